@@ -0,0 +1,59 @@
+//! Hash the source code of the solvers, so that cached answers are only reused when the code that computed them did
+//! not change
+//!
+//! With the `embed-inputs` feature, also list the available inputs so that they are embedded in the binary.
+//!
+//! Also refuse to build without any of the `dayNN` features, which would give a binary that can't solve anything.
+
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            source_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Write the `(path, include_str!(...))` pairs of the inputs which exist, the missing ones are simply left out
+fn embed_inputs() {
+    println!("cargo:rerun-if-changed=inputs");
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let mut code = String::from("&[\n");
+    for day in 1..=25 {
+        let fp = format!("inputs/day{day:02}.txt");
+        let path = manifest_dir.join(&fp);
+        if path.exists() {
+            code.push_str(&format!("    ({fp:?}, include_str!({path:?})),\n"));
+        }
+    }
+    code.push(']');
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("embedded_inputs.rs"), code).unwrap();
+}
+
+fn main() {
+    if !(1..=25).any(|day| env::var_os(format!("CARGO_FEATURE_DAY{day:02}")).is_some()) {
+        panic!("no day to build, enable the `all-days` feature or some `dayNN` features");
+    }
+    println!("cargo:rerun-if-changed=src");
+    let mut files = Vec::new();
+    source_files(Path::new("src"), &mut files);
+    files.sort();
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        file.hash(&mut hasher);
+        fs::read(&file).unwrap().hash(&mut hasher);
+    }
+    println!("cargo:rustc-env=SOURCE_HASH={:016x}", hasher.finish());
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_some() {
+        embed_inputs();
+    }
+}
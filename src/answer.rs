@@ -0,0 +1,130 @@
+//! The answers of the puzzles, keeping their kind so that they can be compared and written out properly
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Answer {
+    /// Most answers are numbers, which can be negative or bigger than 32 bits
+    Integer(i128),
+    Text(String),
+    /// Letters drawn with ASCII art, row by row
+    Grid(Vec<String>),
+    /// The part has nothing to compute, like the second part of the last day
+    None,
+}
+
+impl Answer {
+    /// Whether the answer is the same as the expected one, written as text
+    ///
+    /// Numbers are compared numerically, and the blank space around the text and the grid rows doesn't matter. A part
+    /// without an answer expects none.
+    pub fn matches(&self, expected: &str) -> bool {
+        let expected = expected.trim();
+        match self {
+            Answer::Integer(n) => expected.parse::<i128>() == Ok(*n),
+            Answer::Text(text) => text.trim() == expected,
+            Answer::Grid(rows) => rows
+                .iter()
+                .map(|row| row.trim_end())
+                .eq(expected.lines().map(str::trim_end)),
+            Answer::None => expected.is_empty(),
+        }
+    }
+
+    /// The answer following a label, on its own lines if it's a grid
+    pub fn labelled(&self, label: &str) -> String {
+        match self {
+            Answer::Grid(_) => format!("{label}:\n{self}"),
+            _ => format!("{label}: {self}"),
+        }
+    }
+
+    /// The answer as JSON: a number, a string, an array of rows or `null`
+    ///
+    /// Numbers which don't fit in 64 bits are written as strings, since most JSON readers would round them.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Answer::Integer(n) => match i64::try_from(*n) {
+                Ok(n) => json!(n),
+                Err(_) => json!(n.to_string()),
+            },
+            Answer::Text(text) => json!(text),
+            Answer::Grid(rows) => json!(rows),
+            Answer::None => Value::Null,
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{n}"),
+            Answer::Text(text) => write!(f, "{text}"),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+            Answer::None => write!(f, "(no answer)"),
+        }
+    }
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Answer {
+                    Answer::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Answer {
+        Answer::Text(text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        assert!(Answer::from(42usize).matches(" 42\n"));
+        assert!(Answer::from(-7isize).matches("-7"));
+        assert!(!Answer::from(42u64).matches("42a"));
+        assert!(Answer::from("CMZ").matches("CMZ\n"));
+        let grid = Answer::Grid(vec!["#..#".to_string(), "####".to_string()]);
+        assert!(grid.matches("\n#..#  \n####\n"));
+        assert!(!grid.matches("#..#"));
+        assert!(Answer::None.matches(""));
+        assert!(!Answer::None.matches("0"));
+    }
+
+    #[test]
+    fn output() {
+        let grid = Answer::Grid(vec!["#.".to_string(), ".#".to_string()]);
+        assert_eq!(grid.labelled("Part 2"), "Part 2:\n#.\n.#");
+        assert_eq!(Answer::from(3u8).labelled("Part 1"), "Part 1: 3");
+        assert_eq!(
+            Answer::from(u64::MAX).to_json(),
+            json!(u64::MAX.to_string())
+        );
+        assert_eq!(grid.to_json(), json!(["#.", ".#"]));
+        let json = serde_json::to_string(&Answer::from(1i64 << 40)).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Answer>(&json).unwrap(),
+            Answer::from(1i64 << 40)
+        );
+    }
+}
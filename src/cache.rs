@@ -0,0 +1,108 @@
+//! On-disk cache of the answers, so that running all the days again is instant when neither the code nor the inputs
+//! changed
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::answer::Answer;
+
+const CACHE_FP: &str = ".cache/answers.json";
+
+/// Hash of the source code, computed by the build script
+const SOURCE_HASH: &str = env!("SOURCE_HASH");
+
+/// An answer, with how long it took to compute it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub answer: Answer,
+    pub time_ms: f32,
+}
+
+pub struct Cache {
+    /// Whether cached answers can be used, when `false` the answers are only written
+    read: bool,
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Cache {
+    /// Load the cache file, an unreadable or missing file giving an empty cache
+    pub fn load(read: bool) -> Self {
+        let entries = fs::read_to_string(CACHE_FP)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self { read, entries }
+    }
+
+    /// The key of an answer given by a solver, which changes whenever the source code or the input change
+    pub fn key(day: usize, part: usize, solver: &str, input: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        format!(
+            "day{day:02}/part{part}/{solver}/{SOURCE_HASH}/{:016x}",
+            hasher.finish()
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        if !self.read {
+            return None;
+        }
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, entry: Entry) {
+        self.entries.insert(key, entry);
+    }
+
+    /// Write the cache file, forgetting about the answers computed by another version of the code
+    pub fn save(&mut self) {
+        self.entries
+            .retain(|key, _| key.split('/').nth(3) == Some(SOURCE_HASH));
+        if let Some(dir) = Path::new(CACHE_FP).parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(
+            CACHE_FP,
+            serde_json::to_string_pretty(&self.entries).unwrap(),
+        ) {
+            eprintln!("Could not write the cache: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let key = Cache::key(16, 2, "default", "Valve AA");
+        assert!(key.starts_with(&format!("day16/part2/default/{SOURCE_HASH}/")));
+        assert_eq!(key, Cache::key(16, 2, "default", "Valve AA"));
+        assert_ne!(key, Cache::key(16, 2, "default", "Valve BB"));
+        assert_ne!(key, Cache::key(16, 1, "default", "Valve AA"));
+        assert_ne!(key, Cache::key(16, 2, "other", "Valve AA"));
+    }
+
+    #[test]
+    fn no_read() {
+        let entry = Entry {
+            answer: Answer::Integer(42),
+            time_ms: 1.,
+        };
+        let mut cache = Cache {
+            read: false,
+            entries: BTreeMap::new(),
+        };
+        cache.insert("key".to_string(), entry.clone());
+        assert_eq!(cache.get("key"), None);
+        cache.read = true;
+        assert_eq!(cache.get("key"), Some(&entry));
+    }
+}
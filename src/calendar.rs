@@ -0,0 +1,59 @@
+//! When the puzzles unlock: at midnight US Eastern time (UTC-5), from the 1st to the 25th of December
+
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Utc};
+
+/// The time zone of the puzzles' unlock, daylight saving time being over in December
+fn eastern() -> FixedOffset {
+    FixedOffset::west_opt(5 * 3600).unwrap()
+}
+
+/// The day of the latest puzzle at the given time, whatever the local time zone
+pub fn puzzle_day(now: DateTime<Utc>) -> Option<usize> {
+    let now = now.with_timezone(&eastern());
+    if now.month() == 12 && (1..=25).contains(&now.day()) {
+        Some(now.day() as usize)
+    } else {
+        None
+    }
+}
+
+/// When the puzzle of the given year and day unlocks
+pub fn unlock_time(year: i32, day: usize) -> DateTime<Utc> {
+    eastern()
+        .with_ymd_and_hms(year, 12, day as u32, 0, 0, 0)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// The next puzzle of the given year to unlock after the given time, if any
+pub fn next_unlock(now: DateTime<Utc>, year: i32) -> Option<usize> {
+    (1..=25).find(|&day| unlock_time(year, day) > now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 12, day, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn today() {
+        // in the morning in Europe, the puzzle of the day is out
+        assert_eq!(puzzle_day(utc(5, 5, 30)), Some(5));
+        // in the evening in the Americas, the puzzle of the next day (in UTC) is not out yet
+        assert_eq!(puzzle_day(utc(6, 3, 0)), Some(5));
+        assert_eq!(puzzle_day(utc(1, 4, 59)), None);
+        assert_eq!(puzzle_day(utc(26, 4, 0)), Some(25));
+        assert_eq!(puzzle_day(utc(26, 5, 0)), None);
+    }
+
+    #[test]
+    fn unlocks() {
+        assert_eq!(unlock_time(2022, 1), utc(1, 5, 0));
+        assert_eq!(next_unlock(utc(1, 4, 0), 2022), Some(1));
+        assert_eq!(next_unlock(utc(1, 5, 0), 2022), Some(2));
+        assert_eq!(next_unlock(utc(25, 5, 0), 2022), None);
+    }
+}
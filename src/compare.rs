@@ -0,0 +1,144 @@
+//! Compare the solvers with an external program, for example a solution written in another language
+//!
+//! The program gets the input on its standard input and must print part 1's answer on the first line, then part 2's
+//! answer on the following lines.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use colored::Colorize;
+
+use crate::answer::Answer;
+use crate::days::{self, Day};
+use crate::parser::MyErr;
+use crate::watch::split_answers;
+
+/// An input to compare the solvers on, with a description telling where it comes from
+pub struct Case {
+    pub label: String,
+    pub input: String,
+}
+
+/// Run the command with a shell, giving it the input on its standard input, and return what it printed
+fn run_external(command: &str, input: &str) -> Result<String, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("could not start the program: {e}"))?;
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    // writing from another thread, so that a program printing before it read everything can't block us
+    let writer = thread::spawn(move || {
+        // the program may stop reading early, which is its own business
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("could not run the program: {e}"))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(format!("the program failed ({})", output.status));
+    }
+    String::from_utf8(output.stdout).map_err(|_| "the program's output is not UTF-8".to_string())
+}
+
+/// The parts whose answer differs from the external one, with both answers
+fn mismatches<D: Day>(input: &D::Input, external: &str) -> Vec<(usize, Answer, String)> {
+    let ours: [Answer; 2] = [D::part_1(input).into(), D::part_2(input).into()];
+    ours.into_iter()
+        .zip(split_answers(external))
+        .enumerate()
+        .filter(|(_, (ours, theirs))| !ours.matches(theirs))
+        .map(|(i, (ours, theirs))| (i + 1, ours, theirs))
+        .collect()
+}
+
+/// Compare the answers of the day with the ones of the command on each case, returning whether they all agree
+pub fn compare<D: Day>(command: &str, cases: &[Case]) -> bool {
+    let mut agree = true;
+    for case in cases {
+        let input = match D::parse(&days::normalize(&case.input)) {
+            // the whole input should be understood
+            Ok(("", input)) => input,
+            result => {
+                agree = false;
+                let error = match result {
+                    Ok((rest, _)) => format!(
+                        "could not parse input from: {}",
+                        rest.lines().next().unwrap_or_default()
+                    ),
+                    Err(e) => format!("could not parse input: {}", MyErr::from(e)),
+                };
+                println!("{}: {} {error}", case.label, "error".red());
+                continue;
+            }
+        };
+        let external = match run_external(command, &case.input) {
+            Ok(external) => external,
+            Err(e) => {
+                agree = false;
+                println!("{}: {} {e}", case.label, "error".red());
+                continue;
+            }
+        };
+        let mismatches = mismatches::<D>(&input, &external);
+        if mismatches.is_empty() {
+            println!("{}: {}", case.label, "ok".green());
+            continue;
+        }
+        agree = false;
+        println!("{}: {}", case.label, "mismatch".red());
+        for (part, ours, theirs) in mismatches {
+            println!("  {}", ours.labelled(&format!("part {part}, ours")));
+            println!("  part {part}, theirs: {theirs}");
+        }
+    }
+    agree
+}
+
+#[cfg(all(test, feature = "day01"))]
+mod tests {
+    use super::*;
+    use crate::days::day01::Day01;
+
+    const INPUT: &str = "1000\n2000\n\n3000\n\n500";
+
+    #[test]
+    fn external() {
+        assert_eq!(run_external("cat", "a\nb").unwrap(), "a\nb");
+        assert!(run_external("exit 3", "").is_err());
+    }
+
+    #[test]
+    fn answers() {
+        let (_, input) = Day01::parse(INPUT).unwrap();
+        assert!(mismatches::<Day01>(&input, "3000\n6500\n").is_empty());
+        let mismatches = mismatches::<Day01>(&input, "3000\n6000");
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].0, 2);
+        assert!(!compare::<Day01>(
+            "echo 1",
+            &[Case {
+                label: "test".to_string(),
+                input: INPUT.to_string()
+            }]
+        ));
+    }
+
+    #[test]
+    fn unparsed() {
+        // only the first line can be parsed, which the program agrees with
+        assert!(!compare::<Day01>(
+            "echo 1000; echo 1000",
+            &[Case {
+                label: "test".to_string(),
+                input: "1000\nabc".to_string()
+            }]
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use nom::{
     branch::alt,
     character::complete::{char, line_ending, space1},
@@ -6,6 +7,7 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 
 use crate::days::Day;
 
@@ -56,6 +58,21 @@ impl Day for Day02 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input.iter().map(|(a, x)| format!("{a} {x}")).join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (0..2500 * scale)
+            .map(|_| {
+                (
+                    (b'A' + rng.gen_range(0..3)) as char,
+                    (b'X' + rng.gen_range(0..3)) as char,
+                )
+            })
+            .collect()
+    }
+
     type Output1 = isize;
 
     /// Part 1 took 0.033ms
@@ -93,3 +110,21 @@ impl Day for Day02 {
         score
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            (prop::sample::select(vec!['A', 'B', 'C']), prop::sample::select(vec!['X', 'Y', 'Z'])),
+            0..20,
+        )) {
+            assert_roundtrip::<Day02>(&input);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//use std::collections::HashSet;
+use std::collections::HashSet;
 
 use itertools::Itertools;
 use nom::{
@@ -7,8 +7,9 @@ use nom::{
     multi::separated_list0,
     IResult,
 };
+use rand::{seq::SliceRandom, Rng};
 
-use crate::days::Day;
+use crate::days::{Day, Variant};
 
 pub struct Day03;
 
@@ -31,47 +32,66 @@ impl Day for Day03 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|rucksack| {
+                rucksack
+                    .iter()
+                    .map(|&p| {
+                        if p > 26 {
+                            return (p + 38) as char;
+                        }
+                        (p + 96) as char
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    /// Rucksacks come in groups of three which only have the badge in common, and each rucksack has
+    /// exactly one item type in both compartments
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut rucksacks = Vec::with_capacity(300 * scale);
+        for _ in 0..100 * scale {
+            let mut types = (1..=52).collect_vec();
+            types.shuffle(rng);
+            let badge = types.pop().unwrap();
+            // each remaining item type is missing from one rucksack of the group, so only the badge is common
+            for excluded in types.chunks(types.len() / 3) {
+                let mut available = types
+                    .iter()
+                    .filter(|t| !excluded.contains(t))
+                    .copied()
+                    .collect_vec();
+                available.push(badge);
+                available.shuffle(rng);
+                let shared = available.pop().unwrap();
+                // the other item types are only found in one of the compartments
+                let (left_types, right_types) = available.split_at(available.len() / 2);
+                let half = rng.gen_range(4..=16);
+                let [mut left, mut right] = [left_types, right_types].map(|types| {
+                    let mut items = (1..half).map(|_| *types.choose(rng).unwrap()).collect_vec();
+                    items.push(shared);
+                    items
+                });
+                if left_types.contains(&badge) {
+                    left[0] = badge;
+                } else if right_types.contains(&badge) {
+                    right[0] = badge;
+                }
+                left.shuffle(rng);
+                right.shuffle(rng);
+                left.append(&mut right);
+                rucksacks.push(left);
+            }
+        }
+        rucksacks
+    }
+
     type Output1 = usize;
 
-    /// Cleaner-looking but less performant version
-    /// took 0.2175ms
-    /// ```
-    /// fn part_1(input: &Self::Input) -> Self::Output1 {
-    ///     let mut total = 0;
-    ///     for rs in input {
-    ///         let half = rs.len() / 2;
-    ///         let left: HashSet<&u8> = HashSet::from_iter(rs.iter().take(half));
-    ///         let right: HashSet<&u8> = HashSet::from_iter(rs.iter().skip(half));
-    ///         let mut common = left.intersection(&right);
-    ///         total += **(common.next().unwrap()) as usize
-    ///     }
-    ///     total
-    /// }
-    /// ```
-    ///
-    ///
-    /// First ugly version but quite fast, functional style
-    /// took 0.057ms
-    /// ```
-    /// fn part_1(input: &Self::Input) -> Self::Output1 {
-    ///     input
-    ///         .iter()
-    ///         .map(|rs| {
-    ///             // split each line into 2 equal parts
-    ///             let mid = rs.len() / 2;
-    ///             (rs[..mid].to_vec(), rs[mid..].to_vec())
-    ///         })
-    ///         .flat_map(|rs| {
-    ///             // only keep items from first half that appear in second half (and dedup)
-    ///             rs.0.into_iter().filter(move |e| rs.1.contains(e)).dedup()
-    ///         })
-    ///         .map(|e| e as usize) // cast to usize for summing
-    ///         .sum::<usize>()
-    /// }
-    /// ```
-    ///
-    ///
-    /// A bit faster and looks neater with nested loops
+    /// A bit faster than the `hashset` and `functional` variants, and looks neater with nested loops
     /// Part 1 took 0.0349ms
     fn part_1(input: &Self::Input) -> Self::Output2 {
         let mut total = 0;
@@ -92,54 +112,7 @@ impl Day for Day03 {
 
     type Output2 = usize;
 
-    /// Ugly first version
-    /// took 0.4504ms
-    /// ```
-    /// fn part_2(input: &Self::Input) -> Self::Output2 {
-    ///     let dedup = input
-    ///         .iter()
-    ///         .map(|rs| rs.iter().sorted().dedup().collect_vec()) // dedup each line
-    ///         .collect_vec();
-    ///     let groups = dedup
-    ///         .chunks_exact(3) // loop in groups of 3
-    ///         .map(|gr| {
-    ///             // get how many times each item appears in the concatenated 3 lines
-    ///             let mut counts = gr.concat().into_iter().counts();
-    ///             counts.retain(|_, v| v == &3); // only 1 item should appear thrice
-    ///             let item = counts.keys().next().unwrap(); // the key of that single item is the result
-    ///             **item
-    ///         })
-    ///         .collect_vec();
-    ///     groups.iter().map(|e| *e as usize).sum()
-    /// }
-    /// ```
-    ///
-    ///
-    /// After some optimization, here hashset is beneficial
-    /// took 0.2253ms
-    /// ```
-    /// fn part_2(input: &Self::Input) -> Self::Output2 {
-    ///     let mut total = 0;
-    ///     // we loop in groups of 3
-    ///     for gr in input
-    ///         .iter()
-    ///         .map(|rs| HashSet::from_iter(rs.iter())) // we create hashsets to dedup
-    ///         .collect_vec() // vec of hashsets
-    ///         .chunks_exact(3)
-    ///     {
-    ///         // intersection of the first two sets, collected into another hashset
-    ///         let common: HashSet<_> = gr[0].intersection(&gr[1]).cloned().collect();
-    ///         // intersection with the last set (no collecting, so it's an iterator)
-    ///         let mut common = common.intersection(&gr[2]);
-    ///         // first item in the iterator is the value that was present in all 3 sets
-    ///         total += **(common.next().unwrap()) as usize
-    ///     }
-    ///     total
-    /// }
-    /// ```
-    ///
-    ///
-    /// Finally using `contains` and a nested loop is even better
+    /// Finally using `contains` and a nested loop is even better than the `counts` and `hashset` variants
     /// Part 2 took 0.0206ms
     fn part_2(input: &Self::Input) -> Self::Output2 {
         let mut total = 0;
@@ -154,4 +127,103 @@ impl Day for Day03 {
         }
         total
     }
+
+    fn variants_1() -> Vec<Variant<Self::Input, Self::Output1>> {
+        vec![
+            ("hashset", common_items_hashset),
+            ("functional", common_items_functional),
+        ]
+    }
+
+    fn variants_2() -> Vec<Variant<Self::Input, Self::Output2>> {
+        vec![("counts", badges_counts), ("hashset", badges_hashset)]
+    }
+}
+
+/// Cleaner-looking but less performant version of part 1
+/// took 0.2175ms
+fn common_items_hashset(input: &<Day03 as Day>::Input) -> usize {
+    let mut total = 0;
+    for rs in input {
+        let half = rs.len() / 2;
+        let left: HashSet<&u8> = HashSet::from_iter(rs.iter().take(half));
+        let right: HashSet<&u8> = HashSet::from_iter(rs.iter().skip(half));
+        let mut common = left.intersection(&right);
+        total += **(common.next().unwrap()) as usize
+    }
+    total
+}
+
+/// First ugly version of part 1 but quite fast, functional style
+/// took 0.057ms
+fn common_items_functional(input: &<Day03 as Day>::Input) -> usize {
+    input
+        .iter()
+        .map(|rs| {
+            // split each line into 2 equal parts
+            let mid = rs.len() / 2;
+            (rs[..mid].to_vec(), rs[mid..].to_vec())
+        })
+        .flat_map(|rs| {
+            // only keep items from first half that appear in second half (and dedup)
+            rs.0.into_iter().filter(move |e| rs.1.contains(e)).dedup()
+        })
+        .map(|e| e as usize) // cast to usize for summing
+        .sum::<usize>()
+}
+
+/// Ugly first version of part 2
+/// took 0.4504ms
+fn badges_counts(input: &<Day03 as Day>::Input) -> usize {
+    let dedup = input
+        .iter()
+        .map(|rs| rs.iter().sorted().dedup().collect_vec()) // dedup each line
+        .collect_vec();
+    let groups = dedup
+        .chunks_exact(3) // loop in groups of 3
+        .map(|gr| {
+            // get how many times each item appears in the concatenated 3 lines
+            let mut counts = gr.concat().into_iter().counts();
+            counts.retain(|_, v| v == &3); // only 1 item should appear thrice
+            let item = counts.keys().next().unwrap(); // the key of that single item is the result
+            **item
+        })
+        .collect_vec();
+    groups.iter().map(|e| *e as usize).sum()
+}
+
+/// After some optimization of part 2, here hashset is beneficial
+/// took 0.2253ms
+fn badges_hashset(input: &<Day03 as Day>::Input) -> usize {
+    let mut total = 0;
+    // we loop in groups of 3
+    for gr in input
+        .iter()
+        .map(|rs| HashSet::from_iter(rs.iter())) // we create hashsets to dedup
+        .collect_vec() // vec of hashsets
+        .chunks_exact(3)
+    {
+        // intersection of the first two sets, collected into another hashset
+        let common: HashSet<_> = gr[0].intersection(&gr[1]).cloned().collect();
+        // intersection with the last set (no collecting, so it's an iterator)
+        let mut common = common.intersection(&gr[2]);
+        // first item in the iterator is the value that was present in all 3 sets
+        total += **(common.next().unwrap()) as usize
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(prop::collection::vec(1u8..=52, 1..30), 0..20)) {
+            assert_roundtrip::<Day03>(&input);
+        }
+    }
 }
@@ -1,9 +1,11 @@
+use itertools::Itertools;
 use nom::{
     character::complete::{char, line_ending, u8},
     multi::separated_list0,
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 
 use crate::days::Day;
 
@@ -19,13 +21,13 @@ fn parse_pair(input: &str) -> IResult<&str, Pair> {
     Ok((rest, Pair { first, second }))
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Range {
     pub start: u8,
     pub end: u8,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Pair {
     pub first: Range,
     pub second: Range,
@@ -49,6 +51,34 @@ impl Day for Day04 {
         separated_list0(line_ending, parse_pair)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|p| {
+                format!(
+                    "{}-{},{}-{}",
+                    p.first.start, p.first.end, p.second.start, p.second.end
+                )
+            })
+            .join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut range = || {
+            let (a, b) = (rng.gen_range(1..=99), rng.gen_range(1..=99));
+            Range {
+                start: a.min(b),
+                end: a.max(b),
+            }
+        };
+        (0..1000 * scale)
+            .map(|_| Pair {
+                first: range(),
+                second: range(),
+            })
+            .collect()
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.015ms
@@ -65,3 +95,24 @@ impl Day for Day04 {
         input.iter().fold(0, |acc, p| acc + p.overlaps() as usize)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            any::<(u8, u8, u8, u8)>().prop_map(|(a, b, c, d)| Pair {
+                first: Range { start: a, end: b },
+                second: Range { start: c, end: d },
+            }),
+            0..20,
+        )) {
+            assert_roundtrip::<Day04>(&input);
+        }
+    }
+}
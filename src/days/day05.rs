@@ -1,5 +1,6 @@
 use std::{cell::RefCell, collections::VecDeque};
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -9,21 +10,22 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::days::{Day, Variant};
 
 const NUM_STACKS: usize = 9;
 
 pub struct Day05;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Move {
     pub amount: usize,
     pub from: usize,
     pub to: usize,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct State {
     /// Each stack is represented as a double-ended queue, where the front is the bottom and the back is the top
     pub stacks: RefCell<[VecDeque<char>; NUM_STACKS]>,
@@ -55,7 +57,6 @@ impl State {
     /// In the second part, we move the crates in batches of `amount`.
     ///
     /// Cleaner-looking version where an intermediary VecDeque is created to hold the moved crates.
-    #[allow(dead_code)]
     pub fn move_crates_9001b(&self, amount: usize, from: usize, to: usize) -> &Self {
         let mut stacks = self.stacks.borrow_mut();
         let from_len = stacks[from - 1].len();
@@ -144,6 +145,61 @@ impl Day for Day05 {
         Ok((rest, state))
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        let stacks = input.stacks.borrow();
+        let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+        // draw the crates layer by layer from the top, leaving blank cells where a stack is shorter
+        let mut lines = (0..height)
+            .rev()
+            .map(|layer| {
+                stacks
+                    .iter()
+                    .map(|s| match s.get(layer) {
+                        Some(c) => format!("[{c}]"),
+                        None => "   ".to_string(),
+                    })
+                    .join(" ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect_vec();
+        lines.push((1..=NUM_STACKS).map(|i| format!(" {i} ")).join(" "));
+        let moves = input
+            .moves
+            .iter()
+            .map(|m| format!("move {} from {} to {}", m.amount, m.from, m.to))
+            .join("\n");
+        format!("{}\n\n{moves}", lines.join("\n"))
+    }
+
+    /// The moves never take the last crate of a stack, so that every stack has a top crate at the end
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let state = State::new();
+        for stack in 0..NUM_STACKS {
+            for _ in 0..rng.gen_range(1..=8) {
+                state.push_crate_front(stack, rng.gen_range('A'..='Z'));
+            }
+        }
+        let mut heights = state.stacks.borrow().clone().map(|s| s.len());
+        let mut moves = Vec::with_capacity(500 * scale);
+        while moves.len() < 500 * scale {
+            let from = rng.gen_range(0..NUM_STACKS);
+            let to = rng.gen_range(0..NUM_STACKS);
+            if from == to || heights[from] < 2 {
+                continue;
+            }
+            let amount = rng.gen_range(1..heights[from]).min(30);
+            heights[from] -= amount;
+            heights[to] += amount;
+            moves.push(Move {
+                amount,
+                from: from + 1,
+                to: to + 1,
+            });
+        }
+        State { moves, ..state }
+    }
+
     type Output1 = String;
 
     /// Part 1 took 0.020906ms
@@ -167,4 +223,47 @@ impl Day for Day05 {
         let stacks = state.stacks.borrow();
         stacks.iter().map(|s| s.back().unwrap()).collect()
     }
+
+    fn variants_2() -> Vec<Variant<Self::Input, Self::Output2>> {
+        vec![("split-off", top_crates_9001b)]
+    }
+}
+
+/// Part 2 with the cleaner-looking way of moving the crates
+fn top_crates_9001b(input: &State) -> String {
+    let state = input.clone();
+    for m in &state.moves {
+        state.move_crates_9001b(m.amount, m.from, m.to);
+    }
+    let stacks = state.stacks.borrow();
+    stacks.iter().map(|s| s.back().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(
+            stacks in prop::array::uniform9(prop::collection::vec_deque(prop::char::range('A', 'Z'), 0..8)),
+            moves in prop::collection::vec((any::<u32>(), 1..=9u32, 1..=9u32), 0..20),
+        ) {
+            let input = State {
+                stacks: RefCell::new(stacks),
+                moves: moves
+                    .into_iter()
+                    .map(|(amount, from, to)| Move {
+                        amount: amount as usize,
+                        from: from as usize,
+                        to: to as usize,
+                    })
+                    .collect(),
+            };
+            assert_roundtrip::<Day05>(&input);
+        }
+    }
 }
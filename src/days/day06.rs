@@ -1,12 +1,12 @@
 use itertools::Itertools;
-use nom::IResult;
+use nom::{bytes::complete::take_while1, combinator::map, IResult};
+use rand::{seq::SliceRandom, Rng};
 
-use crate::days::Day;
+use crate::days::{Day, Variant};
 
 pub struct Day06;
 
 /// First easy and clean solution
-#[allow(unused)]
 fn pos_of_unique_pattern(input: &[u8], len: usize) -> usize {
     assert!(len > 0);
     for (i, seq) in input.windows(len).enumerate() {
@@ -36,7 +36,27 @@ impl Day for Day06 {
     type Input = String;
 
     fn parse(input: &str) -> IResult<&str, Self::Input> {
-        Ok(("", String::from(input)))
+        map(take_while1(|c: char| c.is_ascii_lowercase()), String::from)(input)
+    }
+
+    fn unparse(input: &Self::Input) -> String {
+        input.clone()
+    }
+
+    /// The start-of-packet marker is preceded by a part with only 3 different characters, and the
+    /// start-of-message marker by a part with only 13 different characters
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut letters = ('a'..='z').collect_vec();
+        let mut signal = String::with_capacity(4096 * scale);
+        for distinct in [3, 13] {
+            let len = rng.gen_range(500..1500) * scale;
+            letters.shuffle(rng);
+            signal.extend((0..len).map(|_| letters[rng.gen_range(0..distinct)]));
+        }
+        letters.shuffle(rng);
+        signal.extend(&letters[..14]);
+        signal.extend((signal.len()..4096 * scale).map(|_| rng.gen_range('a'..='z')));
+        signal
     }
 
     type Output1 = usize;
@@ -52,4 +72,31 @@ impl Day for Day06 {
     fn part_2(input: &Self::Input) -> Self::Output2 {
         pos_of_unique_pattern2(input.as_bytes(), 14)
     }
+
+    fn variants_1() -> Vec<Variant<Self::Input, Self::Output1>> {
+        vec![("all-unique", |input| {
+            pos_of_unique_pattern(input.as_bytes(), 4)
+        })]
+    }
+
+    fn variants_2() -> Vec<Variant<Self::Input, Self::Output2>> {
+        vec![("all-unique", |input| {
+            pos_of_unique_pattern(input.as_bytes(), 14)
+        })]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in "[a-z]{1,100}") {
+            assert_roundtrip::<Day06>(&input);
+        }
+    }
 }
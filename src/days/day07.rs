@@ -10,12 +10,16 @@ use nom::{
     sequence::{pair, separated_pair},
     IResult,
 };
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
 
 use crate::days::Day;
 
 pub struct Day07;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LogItem {
     Change(String),
     List,
@@ -77,6 +81,43 @@ fn get_sizes(input: &<Day07 as Day>::Input) -> HashMap<String, usize> {
     sizes
 }
 
+/// Append the listing of a random directory containing `num_dirs` directories (itself included) to the log,
+/// then visit its subdirectories
+fn generate_dir<R: Rng>(rng: &mut R, num_dirs: usize, log: &mut Vec<LogItem>) {
+    log.push(LogItem::List);
+    let mut names = Vec::new();
+    let num_subdirs = rng.gen_range(1..=4).min(num_dirs - 1);
+    while names.len() < num_subdirs {
+        let len = rng.gen_range(1..=8);
+        let name: String = (0..len).map(|_| rng.gen_range('a'..='z')).collect();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let mut entries = names.iter().cloned().map(LogItem::Dir).collect_vec();
+    entries
+        .extend((0..rng.gen_range(0..=5)).map(|_| LogItem::File(rng.gen_range(1_000..=300_000))));
+    entries.shuffle(rng);
+    log.extend(entries);
+    // share the remaining directories between the subdirectories, each getting at least one
+    let mut cuts = sample(
+        rng,
+        num_dirs.saturating_sub(2),
+        num_subdirs.saturating_sub(1),
+    )
+    .into_iter()
+    .map(|cut| cut + 1)
+    .sorted()
+    .collect_vec();
+    cuts.insert(0, 0);
+    cuts.push(num_dirs - 1);
+    for (name, (start, end)) in names.into_iter().zip(cuts.into_iter().tuple_windows()) {
+        log.push(LogItem::Change(name));
+        generate_dir(rng, end - start, log);
+        log.push(LogItem::Change("..".to_string()));
+    }
+}
+
 fn get_total_size(input: &<Day07 as Day>::Input) -> usize {
     input.iter().fold(0, |acc, i| match i {
         LogItem::File(size) => acc + size,
@@ -91,6 +132,35 @@ impl Day for Day07 {
         separated_list0(line_ending, parse_line)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match item {
+                LogItem::Change(dir) => format!("$ cd {dir}"),
+                LogItem::List => "$ ls".to_string(),
+                LogItem::Dir(dir) => format!("dir {dir}"),
+                // file names are not kept in the parsed input, so we make up unique ones
+                LogItem::File(size) => format!("{size} file{i}.txt"),
+            })
+            .join("\n")
+    }
+
+    /// The directory tree is random, then the file sizes are adjusted so that between 41M and 49M of the disk
+    /// is used, like in the real puzzle input
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut log = vec![LogItem::Change("/".to_string())];
+        generate_dir(rng, 180 * scale, &mut log);
+        let total = get_total_size(&log);
+        let target = rng.gen_range(41_000_000..=49_000_000);
+        for item in &mut log {
+            if let LogItem::File(size) = item {
+                *size = (*size * target / total).max(1);
+            }
+        }
+        log
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.073969ms
@@ -122,3 +192,28 @@ impl Day for Day07 {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn log_item() -> impl Strategy<Value = LogItem> {
+        prop_oneof![
+            prop_oneof![Just("/".to_string()), Just("..".to_string()), "[a-z]{1,8}"]
+                .prop_map(LogItem::Change),
+            Just(LogItem::List),
+            "[a-z]{1,8}".prop_map(LogItem::Dir),
+            any::<u32>().prop_map(|size| LogItem::File(size as usize)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(log_item(), 0..30)) {
+            assert_roundtrip::<Day07>(&input);
+        }
+    }
+}
@@ -5,12 +5,13 @@ use nom::{
     multi::separated_list0,
     IResult,
 };
+use rand::Rng;
 
 use crate::days::Day;
 
 pub struct Day08;
 
-fn transpose<T>(v: &Vec<Vec<T>>) -> Vec<Vec<T>>
+fn transpose<T>(v: &[Vec<T>]) -> Vec<Vec<T>>
 where
     T: Clone,
 {
@@ -32,6 +33,29 @@ impl Day for Day08 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&tree| (tree + 48) as char)
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let size = 99 * scale;
+        (0..size)
+            .map(|_| (0..size).map(|_| rng.gen_range(0..=9)).collect())
+            .collect()
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let width = input.first().map_or(0, Vec::len);
+        vec![("grid", format!("{width}x{}", input.len()))]
+    }
+
     type Output1 = usize;
 
     /// The naive solution below is not very efficient, it takes 1.2ms to run
@@ -167,3 +191,18 @@ impl Day for Day08 {
         *score.iter().flatten().max().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(prop::collection::vec(0u8..=9, 1..20), 0..20)) {
+            assert_roundtrip::<Day08>(&input);
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use std::{cell::RefCell, collections::HashSet};
+use std::collections::HashSet;
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -9,12 +10,16 @@ use nom::{
     sequence::pair,
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::{
+    days::Day,
+    geom::{Direction, Point},
+};
 
 pub struct Day09;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Move {
     Up(isize),
     Right(isize),
@@ -31,6 +36,18 @@ impl Move {
             Self::Left(dist) => *dist,
         }
     }
+
+    /// Direction of a move increment
+    ///
+    /// The puzzle has y pointing to the top, but since we only count visited positions, the orientation doesn't matter.
+    fn dir(&self) -> Direction {
+        match self {
+            Self::Up(_) => Direction::Up,
+            Self::Right(_) => Direction::Right,
+            Self::Down(_) => Direction::Down,
+            Self::Left(_) => Direction::Left,
+        }
+    }
 }
 
 impl From<(&str, i64)> for Move {
@@ -45,103 +62,13 @@ impl From<(&str, i64)> for Move {
     }
 }
 
-/// we will use interior mutability to avoid having to borrow mutably
-#[derive(Clone)]
-struct Point {
-    x: RefCell<isize>,
-    y: RefCell<isize>,
-}
-
-impl Default for Point {
-    fn default() -> Self {
-        Self {
-            x: RefCell::new(0),
-            y: RefCell::new(0),
-        }
-    }
-}
-
-/// x points to the right
-/// y points to the top
-impl Point {
-    fn x(&self) -> isize {
-        *self.x.borrow()
-    }
-
-    fn y(&self) -> isize {
-        *self.y.borrow()
-    }
-
-    /// Move this point by a signed increment in x and y
-    fn mov(&self, x: isize, y: isize) -> &Self {
-        *self.x.borrow_mut() += x;
-        *self.y.borrow_mut() += y;
-        self
-    }
-
-    /// Apply a move increment (distance = 1)
-    fn apply_move(&self, mov: &Move) -> &Self {
-        match mov {
-            Move::Up(_) => {
-                self.mov(0, 1);
-            }
-            Move::Right(_) => {
-                self.mov(1, 0);
-            }
-            Move::Down(_) => {
-                self.mov(0, -1);
-            }
-            Move::Left(_) => {
-                self.mov(-1, 0);
-            }
-        }
-        self
-    }
-
-    /// Move this point towards another point
-    fn move_towards(&self, other: &Point) -> &Self {
-        if other.x() > self.x() {
-            *self.x.borrow_mut() += 1;
-        }
-        if other.x() < self.x() {
-            *self.x.borrow_mut() -= 1;
-        }
-        if other.y() > self.y() {
-            *self.y.borrow_mut() += 1;
-        }
-        if other.y() < self.y() {
-            *self.y.borrow_mut() -= 1;
-        }
-        self
-    }
-
-    /// Compte the distance between two points
-    ///
-    /// The actual distance is computed as follows:
-    ///
-    /// ```
-    /// fn dist_real(&self, other: &Point) -> f64 {
-    ///     let dx = self.x() - other.x();
-    ///     let dy = self.y() - other.y();
-    ///     let m = (dx.pow(2) + dy.pow(2)) as f64;
-    ///     m.sqrt()
-    /// }
-    /// ```
-    ///
-    /// But we can simplify it in our case, since we can't move more than 1
-    /// position at a time
-    ///
-    fn dist(&self, other: &Point) -> isize {
-        let dx = self.x() - other.x();
-        let dy = self.y() - other.y();
-        /* if dx == 0 && dy == 0 {
-            // this is not even needed as we just want to know if the distance is larger than 1
-            return 0;
-        } */
-        if dx.abs() > 1 || dy.abs() > 1 {
-            return 2;
-        }
-        1 // for our purposes, 0 or 1 is the same
+/// Move a knot towards the one in front of it, if it is too far away
+///
+/// We can't move more than 1 position at a time, so the knots are touching as long as their Chebyshev distance is at
+/// most 1.
+fn follow(knot: &mut Point<isize>, leader: &Point<isize>) {
+    if knot.chebyshev(leader) > 1 {
+        *knot += (*leader - *knot).signum();
     }
 }
 
@@ -160,23 +87,51 @@ impl Day for Day09 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|mov| {
+                let dir = match mov {
+                    Move::Up(_) => 'U',
+                    Move::Right(_) => 'R',
+                    Move::Down(_) => 'D',
+                    Move::Left(_) => 'L',
+                };
+                format!("{dir} {}", mov.dist())
+            })
+            .join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (0..2000 * scale)
+            .map(|_| {
+                let dist = rng.gen_range(1..=20);
+                match rng.gen_range(0..4) {
+                    0 => Move::Up(dist),
+                    1 => Move::Right(dist),
+                    2 => Move::Down(dist),
+                    _ => Move::Left(dist),
+                }
+            })
+            .collect()
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.4943ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
         // keep track of all the visited coordinates
-        let mut visited: HashSet<(isize, isize)> = HashSet::new();
-        let head = Point::default();
-        let tail = Point::default();
+        let mut visited: HashSet<Point<isize>> = HashSet::new();
+        let mut head = Point::default();
+        let mut tail = Point::default();
         for mov in input {
             for _ in 0..mov.dist() {
                 // for each movement step
-                head.apply_move(mov); // only moves by 1 unit
-                if head.dist(&tail) > 1 {
-                    // the head moved too far, we need to move the tail too
-                    tail.move_towards(&head);
-                }
-                visited.insert((tail.x(), tail.y()));
+                // the head only moves by 1 unit
+                head = head.step(mov.dir());
+                // the head might have moved too far, we need to move the tail too
+                follow(&mut tail, &head);
+                visited.insert(tail);
             }
         }
         visited.len()
@@ -187,20 +142,46 @@ impl Day for Day09 {
     /// Part 2 took 0.6268ms
     fn part_2(input: &Self::Input) -> Self::Output2 {
         // keep track of all the visited coordinates
-        let mut visited: HashSet<(isize, isize)> = HashSet::new();
-        let knots = vec![Point::default(); 10];
+        let mut visited: HashSet<Point<isize>> = HashSet::new();
+        let mut knots = [Point::default(); 10];
         for mov in input {
             for _ in 0..mov.dist() {
                 // for each movement step
-                knots[0].apply_move(mov); // only moves by 1 unit
+                knots[0] = knots[0].step(mov.dir()); // only moves by 1 unit
                 for k in 1..10 {
-                    if knots[k - 1].dist(&knots[k]) > 1 {
-                        knots[k].move_towards(&knots[k - 1]);
-                    }
+                    let leader = knots[k - 1];
+                    follow(&mut knots[k], &leader);
                 }
-                visited.insert((knots[9].x(), knots[9].y()));
+                visited.insert(knots[9]);
             }
         }
         visited.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn mov() -> impl Strategy<Value = Move> {
+        (0..4, any::<i32>()).prop_map(|(dir, dist)| {
+            let dist = dist as isize;
+            match dir {
+                0 => Move::Up(dist),
+                1 => Move::Right(dist),
+                2 => Move::Down(dist),
+                _ => Move::Left(dist),
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(mov(), 0..30)) {
+            assert_roundtrip::<Day09>(&input);
+        }
+    }
+}
@@ -8,12 +8,14 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 
+use crate::answer::Answer;
 use crate::days::Day;
 
 pub struct Day10;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     Noop,
     Addx(isize),
@@ -67,6 +69,41 @@ impl Day for Day10 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|instr| match instr {
+                Instruction::Noop => "noop".to_string(),
+                Instruction::Addx(val) => format!("addx {val}"),
+            })
+            .join("\n")
+    }
+
+    /// The CRT has a fixed size, so the program always takes exactly 240 cycles and `scale` is ignored
+    ///
+    /// The register stays between 2 and 38 so that the sprite is always fully on screen.
+    fn generate<R: Rng>(rng: &mut R, _scale: usize) -> Self::Input {
+        let mut instructions = vec![];
+        let mut cycles = 0;
+        let mut x = 1;
+        while cycles < 240 {
+            if cycles == 239 || rng.gen_bool(0.3) {
+                instructions.push(Instruction::Noop);
+                cycles += 1;
+                continue;
+            }
+            // an `addx 0` would only take one cycle in our solver
+            let mut target = x;
+            while target == x {
+                target = rng.gen_range(2..=38);
+            }
+            instructions.push(Instruction::Addx(target - x));
+            x = target;
+            cycles += 2;
+        }
+        instructions
+    }
+
     type Output1 = isize;
 
     /// Part 1 took 0.002ms
@@ -89,7 +126,7 @@ impl Day for Day10 {
         signal_sum
     }
 
-    type Output2 = String;
+    type Output2 = Answer;
 
     /// Part 2 took 0.0061ms
     fn part_2(input: &Self::Input) -> Self::Output2 {
@@ -110,11 +147,31 @@ impl Day for Day10 {
                 break;
             }
         }
-        let mut res = crt
-            .chunks_exact(40)
-            .map(|row| row.iter().collect::<String>())
-            .join("\n");
-        res.insert(0, '\n');
-        res
+        Answer::Grid(
+            crt.chunks_exact(40)
+                .map(|row| row.iter().collect())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            prop_oneof![
+                Just(Instruction::Noop),
+                any::<i32>().prop_map(|v| Instruction::Addx(v as isize)),
+            ],
+            0..30,
+        )) {
+            assert_roundtrip::<Day10>(&input);
+        }
     }
 }
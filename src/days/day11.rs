@@ -4,22 +4,23 @@ use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{anychar, char, newline, u64, u8},
+    character::complete::{anychar, char, newline, u64},
     combinator::map,
     multi::separated_list0,
     sequence::tuple,
     IResult,
 };
+use rand::{seq::SliceRandom, Rng};
 
 use crate::days::Day;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     Mult,
     Add,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Operand {
     Value(usize),
     Old,
@@ -36,7 +37,7 @@ impl Operand {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Monkey {
     pub id: usize,
     pub items: RefCell<VecDeque<usize>>,
@@ -59,7 +60,7 @@ impl Monkey {
 }
 
 fn parse_id(input: &str) -> IResult<&str, usize> {
-    map(tuple((tag("Monkey "), u8, tag(":\n"))), |(_, id, _)| {
+    map(tuple((tag("Monkey "), u64, tag(":\n"))), |(_, id, _)| {
         id as usize
     })(input)
 }
@@ -103,14 +104,14 @@ fn parse_modulo(input: &str) -> IResult<&str, usize> {
 
 fn parse_true(input: &str) -> IResult<&str, usize> {
     map(
-        tuple((tag("    If true: throw to monkey "), u8, newline)),
+        tuple((tag("    If true: throw to monkey "), u64, newline)),
         |(_, t, _)| t as usize,
     )(input)
 }
 
 fn parse_false(input: &str) -> IResult<&str, usize> {
     map(
-        tuple((tag("    If false: throw to monkey "), u8)),
+        tuple((tag("    If false: throw to monkey "), u64)),
         |(_, f)| f as usize,
     )(input)
 }
@@ -136,6 +137,35 @@ fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
     Ok((rest, monkey))
 }
 
+/// Check if the worry levels go out of range in part 1, where they are not kept in check with a modulo
+fn part_1_overflows(monkeys: &[Monkey]) -> bool {
+    let mut items = monkeys
+        .iter()
+        .map(|m| m.items.borrow().iter().copied().collect_vec())
+        .collect_vec();
+    for _ in 0..20 {
+        for monkey in monkeys {
+            for worry_level in std::mem::take(&mut items[monkey.id]) {
+                let operand = monkey.operand.as_value(worry_level);
+                let during_inspection = match monkey.operator {
+                    Operator::Mult => worry_level.checked_mul(operand),
+                    Operator::Add => worry_level.checked_add(operand),
+                };
+                let Some(during_inspection) = during_inspection else {
+                    return true;
+                };
+                let after_inspection = during_inspection / 3;
+                if after_inspection % monkey.modulo == 0 {
+                    items[monkey.throw_true].push(after_inspection);
+                } else {
+                    items[monkey.throw_false].push(after_inspection);
+                }
+            }
+        }
+    }
+    false
+}
+
 pub struct Day11;
 
 fn process_monkeys(
@@ -173,6 +203,88 @@ impl Day for Day11 {
         separated_list0(tag("\n\n"), parse_monkey)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|monkey| {
+                let operator = match monkey.operator {
+                    Operator::Mult => '*',
+                    Operator::Add => '+',
+                };
+                let operand = match monkey.operand {
+                    Operand::Old => "old".to_string(),
+                    Operand::Value(v) => v.to_string(),
+                };
+                format!(
+                    "Monkey {}:\n  Starting items: {}\n  Operation: new = old {operator} {operand}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}",
+                    monkey.id,
+                    monkey.items.borrow().iter().join(", "),
+                    monkey.modulo,
+                    monkey.throw_true,
+                    monkey.throw_false
+                )
+            })
+            .join("\n\n")
+    }
+
+    /// There are always 8 monkeys with distinct prime divisors, so that the common modulo stays small, and `scale`
+    /// gives the number of starting items
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        loop {
+            let mut divisors = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+            divisors.shuffle(rng);
+            // one monkey squares the worry level, a couple multiply it and the others add to it
+            let mut operations = vec![(Operator::Mult, Operand::Old)];
+            operations
+                .extend((0..2).map(|_| (Operator::Mult, Operand::Value(rng.gen_range(2..=19)))));
+            operations
+                .extend((0..5).map(|_| (Operator::Add, Operand::Value(rng.gen_range(1..=8)))));
+            operations.shuffle(rng);
+            let monkeys = operations
+                .into_iter()
+                .zip(divisors)
+                .enumerate()
+                .map(|(id, ((operator, operand), modulo))| {
+                    let others = (0..8).filter(|&other| other != id).collect_vec();
+                    let throws = others.choose_multiple(rng, 2).collect_vec();
+                    Monkey {
+                        id,
+                        items: RefCell::new(
+                            (0..rng.gen_range(1..=8) * scale)
+                                .map(|_| rng.gen_range(50..=99))
+                                .collect(),
+                        ),
+                        operator,
+                        operand,
+                        modulo,
+                        throw_true: *throws[0],
+                        throw_false: *throws[1],
+                    }
+                })
+                .collect_vec();
+            if !part_1_overflows(&monkeys) {
+                return monkeys;
+            }
+        }
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let items: usize = input.iter().map(|m| m.items.borrow().len()).sum();
+        vec![
+            ("monkeys", input.len().to_string()),
+            ("items", items.to_string()),
+            ("divisors", input.iter().map(|m| m.modulo).join(", ")),
+            (
+                "divisors product",
+                input
+                    .iter()
+                    .map(|m| m.modulo)
+                    .product::<usize>()
+                    .to_string(),
+            ),
+        ]
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.027ms
@@ -198,3 +310,44 @@ impl Day for Day11 {
         inspections.iter().sorted().rev().take(2).product()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn monkey() -> impl Strategy<Value = Monkey> {
+        (
+            any::<u16>(),
+            prop::collection::vec_deque(any::<u32>().prop_map(|i| i as usize), 0..10),
+            prop_oneof![Just(Operator::Mult), Just(Operator::Add)],
+            prop_oneof![
+                Just(Operand::Old),
+                any::<u32>().prop_map(|v| Operand::Value(v as usize))
+            ],
+            any::<u32>(),
+            any::<u16>(),
+            any::<u16>(),
+        )
+            .prop_map(
+                |(id, items, operator, operand, modulo, throw_true, throw_false)| Monkey {
+                    id: id as usize,
+                    items: RefCell::new(items),
+                    operator,
+                    operand,
+                    modulo: modulo as usize,
+                    throw_true: throw_true as usize,
+                    throw_false: throw_false as usize,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(monkey(), 0..8)) {
+            assert_roundtrip::<Day11>(&input);
+        }
+    }
+}
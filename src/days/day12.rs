@@ -1,32 +1,31 @@
 use std::{
     cmp::Ordering,
     collections::{BinaryHeap, HashMap, VecDeque},
-    io::{self, Write},
+    fmt::Write,
 };
 
 use colored::Colorize;
 use itertools::Itertools;
 use nom::{
-    character::complete::{line_ending, not_line_ending},
-    combinator::map,
-    multi::separated_list0,
+    character::complete::{line_ending, satisfy},
+    combinator::{map, verify},
+    multi::{many1, separated_list1},
     IResult,
 };
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
+use tracing::{debug, Level};
 
-use crate::days::Day;
+use crate::{
+    days::Day,
+    geom::{self, Direction},
+    repl::arg,
+};
 
-#[derive(Debug, PartialEq, Eq, Default, Clone, Hash)]
-pub struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    /// Calculate the Manhattan distance to another point
-    pub fn distance_to(&self, other: &Point) -> usize {
-        (self.x.max(other.x) - self.x.min(other.x)) + (self.y.max(other.y) - self.y.min(other.y))
-    }
-}
+/// x points to the right (column index), y points to the bottom (row index)
+type Point = geom::Point<usize>;
 
 /// An open position, with its coordinates as a `Point` and the f-score or expected cost to reach the end position.
 #[derive(Debug)]
@@ -37,45 +36,13 @@ pub struct OpenPos {
 
 impl OpenPos {
     /// Check which of the 2-4 neighbors are valid moves and return them in a Vec.
-    pub fn valid_neighbors(&self, grid: &Vec<Vec<usize>>) -> Vec<Point> {
-        let mut n = Vec::<Point>::with_capacity(4);
-        // top
-        if self.point.x > 0
-            && grid[self.point.x - 1][self.point.y] <= grid[self.point.x][self.point.y] + 1
-        {
-            n.push(Point {
-                x: self.point.x - 1,
-                y: self.point.y,
-            })
-        }
-        // right
-        if self.point.y < grid[0].len() - 1
-            && grid[self.point.x][self.point.y + 1] <= grid[self.point.x][self.point.y] + 1
-        {
-            n.push(Point {
-                x: self.point.x,
-                y: self.point.y + 1,
-            })
-        }
-        // bottom
-        if self.point.x < grid.len() - 1
-            && grid[self.point.x + 1][self.point.y] <= grid[self.point.x][self.point.y] + 1
-        {
-            n.push(Point {
-                x: self.point.x + 1,
-                y: self.point.y,
-            })
-        }
-        // left
-        if self.point.y > 0
-            && grid[self.point.x][self.point.y - 1] <= grid[self.point.x][self.point.y] + 1
-        {
-            n.push(Point {
-                x: self.point.x,
-                y: self.point.y - 1,
-            })
-        }
-        n
+    pub fn valid_neighbors(&self, grid: &[Vec<usize>]) -> Vec<Point> {
+        let elevation = grid[self.point.y][self.point.x];
+        Direction::ALL
+            .into_iter()
+            .filter_map(|dir| self.point.checked_step(dir, grid[0].len(), grid.len()))
+            .filter(|n| grid[n.y][n.x] <= elevation + 1)
+            .collect()
     }
 }
 
@@ -89,13 +56,13 @@ impl PartialEq for OpenPos {
 
 impl PartialOrd for OpenPos {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.cost.partial_cmp(&self.cost)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for OpenPos {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        other.cost.cmp(&self.cost)
     }
 }
 
@@ -108,8 +75,8 @@ pub struct Day12;
 fn find_start_end(input: &mut <Day12 as Day>::Input) -> (Point, Point) {
     let mut start = Point::default();
     let mut end = Point::default();
-    for (x, row) in input.iter_mut().enumerate() {
-        for (y, cell) in row.iter_mut().enumerate() {
+    for (y, row) in input.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
             if *cell == 'S' as usize {
                 start = Point { x, y };
                 let val = 'a' as usize;
@@ -124,64 +91,72 @@ fn find_start_end(input: &mut <Day12 as Day>::Input) -> (Point, Point) {
 }
 
 /// Reconstruct the path from the end point and the map of where points were reach from
-fn path(came_from: HashMap<Point, Point>, current: Point) -> VecDeque<Point> {
+fn path(came_from: &HashMap<Point, Point>, current: Point) -> VecDeque<Point> {
     let mut path: VecDeque<Point> = VecDeque::new();
-    path.push_front(current.clone());
+    path.push_front(current);
     let mut current = current;
     while came_from.contains_key(&current) {
-        current = came_from.get(&current).unwrap().clone();
-        path.push_front(current.clone())
+        current = *came_from.get(&current).unwrap();
+        path.push_front(current)
     }
     path
 }
 
-/// Print a colorful representation of the path in the grid
-fn print_path(path: &VecDeque<Point>, grid: &[Vec<usize>]) {
+/// Draw a colorful representation of the path in the grid
+///
+/// Without colors, the letters on the path are written in uppercase instead.
+fn render_path(path: &VecDeque<Point>, grid: &[Vec<usize>]) -> String {
     let gradient = colorous::PLASMA;
-    let stdout = io::stdout();
-    let mut handle = io::BufWriter::new(stdout);
-    for (x, row) in grid.iter().enumerate() {
-        for (y, cell) in row.iter().enumerate() {
+    let colors = colored::control::SHOULD_COLORIZE.should_colorize();
+    let mut out = String::new();
+    for (y, row) in grid.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
             let point = Point { x, y };
             let color = gradient.eval_rational(*cell - 97, 26);
-            if path.contains(&point) {
-                let _ = write!(
-                    handle,
+            let c = char::from_u32(*cell as u32).unwrap();
+            let _ = match (path.contains(&point), colors) {
+                (true, true) => write!(
+                    out,
                     "{}",
-                    char::from_u32(*cell as u32)
-                        .unwrap()
-                        .to_string()
+                    c.to_string()
                         .on_truecolor(color.r, color.g, color.b)
                         .truecolor(255, 255, 255)
-                );
-            } else {
-                let _ = write!(
-                    handle,
+                ),
+                (false, true) => write!(
+                    out,
                     "{}",
-                    char::from_u32(*cell as u32)
-                        .unwrap()
-                        .to_string()
+                    c.to_string()
                         .on_truecolor(color.r, color.g, color.b)
                         .truecolor(0, 0, 0)
-                );
-            }
+                ),
+                (true, false) => write!(out, "{}", c.to_ascii_uppercase()),
+                (false, false) => write!(out, "{c}"),
+            };
         }
-        let _ = writeln!(handle);
+        out.push('\n');
     }
+    out
 }
 
-/// Implement the A* algorithm, returning the number of steps in the shortest path.
+/// Log the path at the debug level, drawing it only when it will be shown
+fn debug_path(path: &VecDeque<Point>, grid: &[Vec<usize>]) {
+    if tracing::enabled!(Level::DEBUG) {
+        debug!("shortest path:\n{}", render_path(path, grid));
+    }
+}
+
+/// Implement the A* algorithm, returning the shortest path, from the start to the end.
 ///
 /// This uses the Manhattan distance to the end node as the cost function.
 /// The grid contains the elevation for each node.
-fn a_star(grid: &Vec<Vec<usize>>, start: Point, end: &Point, print: bool) -> Option<usize> {
+fn a_star(grid: &[Vec<usize>], start: Point, end: &Point) -> Option<VecDeque<Point>> {
     // the open_set is the list of all candidates for the next move.
     // this is a min heap (sorting on the cost attribute)
     let mut open_set = BinaryHeap::<OpenPos>::new();
     // the starting point gets added as the only option for the first move
     open_set.push(OpenPos {
-        point: start.clone(),
-        cost: start.distance_to(end), // f-score, which is the expected cost to reach the End node
+        point: start,
+        cost: start.manhattan(end), // f-score, which is the expected cost to reach the End node
     });
     // this map stores the path dependencies so we can reconstruct it later
     let mut came_from = HashMap::<Point, Point>::new();
@@ -194,11 +169,7 @@ fn a_star(grid: &Vec<Vec<usize>>, start: Point, end: &Point, print: bool) -> Opt
     while let Some(current) = open_set.pop() {
         // in case we reached the end, we can end the algo and reconstruct the path
         if current.point == *end {
-            let path = path(came_from, current.point);
-            if print {
-                print_path(&path, grid);
-            }
-            return Some(path.len() - 1); // the number of steps is the length - 1
+            return Some(path(&came_from, current.point));
         }
 
         // for each elligible neighbor (with at most 1 more height as current node)
@@ -210,14 +181,14 @@ fn a_star(grid: &Vec<Vec<usize>>, start: Point, end: &Point, print: bool) -> Opt
             // inequality always is `true`
             if tentative_gscore < *g_score.get(n).unwrap_or(&usize::MAX) {
                 // store from which node we came (might get overwritten later)
-                came_from.insert(n.clone(), current.point.clone());
+                came_from.insert(*n, current.point);
                 // save the g-score for this neighbor
-                g_score.insert(n.clone(), tentative_gscore);
+                g_score.insert(*n, tentative_gscore);
                 // save this neighbor as a candidate, calculating its f-score by adding the expected cost until we
                 // reach the End node to the already travelled distance.
                 let pos = OpenPos {
-                    point: n.clone(),
-                    cost: tentative_gscore + n.distance_to(end), // f-score
+                    point: *n,
+                    cost: tentative_gscore + n.manhattan(end), // f-score
                 };
                 // since we want to replace this point in the min-heap if it exists, we need to remove it first
                 open_set.retain(|p| p.point != pos.point);
@@ -236,12 +207,100 @@ impl Day for Day12 {
     type Input = Vec<Vec<usize>>;
 
     fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
+        let rows = separated_list1(
             line_ending,
-            map(not_line_ending, |s: &str| {
-                s.chars().map(|c| c as usize).collect_vec()
-            }),
-        )(input)
+            many1(map(
+                satisfy(|c| c.is_ascii_lowercase() || c == 'S' || c == 'E'),
+                |c| c as usize,
+            )),
+        );
+        // there must be a start and an end
+        verify(rows, |rows: &Vec<Vec<usize>>| {
+            ['S', 'E']
+                .iter()
+                .all(|&c| rows.iter().flatten().any(|&h| h == c as usize))
+        })(input)
+    }
+
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| char::from_u32(c as u32).unwrap())
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    /// The elevation rises from `a` to `z` along a random path from `S` to `E`, so that there is always a solution,
+    /// and the other positions have a random elevation
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let (width, height) = (80 * scale, 41);
+        let start = Point::new(0, rng.gen_range(0..height));
+        // visit the whole grid in a random depth-first order, remembering where each position was reached from
+        let mut came_from = HashMap::<Point, Point>::new();
+        let mut visited = vec![vec![false; width]; height];
+        visited[start.y][start.x] = true;
+        let mut stack = vec![start];
+        while let Some(&current) = stack.last() {
+            let unvisited = Direction::ALL
+                .into_iter()
+                .filter_map(|dir| current.checked_step(dir, width, height))
+                .filter(|n| !visited[n.y][n.x])
+                .collect_vec();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next.y][next.x] = true;
+                    came_from.insert(next, current);
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        // the end must be far enough from the start to climb all the way up
+        let path = loop {
+            let end = Point::new(rng.gen_range(0..width), rng.gen_range(0..height));
+            let path = path(&came_from, end);
+            if path.len() > 25 {
+                break path;
+            }
+        };
+        let mut grid = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| rng.gen_range('a'..='z') as usize)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let climbs = sample(rng, path.len() - 1, 25).into_vec();
+        let mut elevation = 'a' as usize;
+        for (i, point) in path.iter().enumerate().skip(1) {
+            if climbs.contains(&(i - 1)) {
+                elevation += 1;
+            }
+            grid[point.y][point.x] = elevation;
+        }
+        grid[start.y][start.x] = 'S' as usize;
+        let end = path.back().unwrap();
+        grid[end.y][end.x] = 'E' as usize;
+        grid
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let width = input.first().map_or(0, Vec::len);
+        // the possible starting points of part 2
+        let lowest = input
+            .iter()
+            .flatten()
+            .filter(|&&c| c == 'a' as usize || c == 'S' as usize)
+            .count();
+        vec![
+            ("grid", format!("{width}x{}", input.len())),
+            ("lowest squares", lowest.to_string()),
+        ]
     }
 
     type Output1 = usize;
@@ -250,7 +309,9 @@ impl Day for Day12 {
     fn part_1(input: &Self::Input) -> Self::Output1 {
         let mut grid = input.clone();
         let (start, end) = find_start_end(&mut grid);
-        a_star(&grid, start, &end, true).unwrap()
+        let path = a_star(&grid, start, &end).unwrap();
+        debug_path(&path, &grid);
+        path.len() - 1 // the number of steps is the length - 1
     }
 
     type Output2 = usize;
@@ -260,18 +321,65 @@ impl Day for Day12 {
         let mut grid = input.clone();
         let (_, end) = find_start_end(&mut grid);
         let mut lengths = Vec::<(usize, Point)>::with_capacity(1000);
-        for (x, row) in grid.iter().enumerate() {
-            for (y, cell) in row.iter().enumerate() {
+        for (y, row) in grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
                 if *cell != 'a' as usize {
                     continue;
                 }
-                if let Some(steps) = a_star(&grid, Point { x, y }, &end, false) {
-                    lengths.push((steps, Point { x, y }))
+                if let Some(path) = a_star(&grid, Point { x, y }, &end) {
+                    lengths.push((path.len() - 1, Point { x, y }))
                 }
             }
         }
         let min = lengths.iter().min_by(|a, b| a.0.cmp(&b.0)).unwrap();
-        a_star(&grid, min.1.clone(), &end, true);
+        if tracing::enabled!(Level::DEBUG) {
+            debug_path(&a_star(&grid, min.1, &end).unwrap(), &grid);
+        }
         min.0
     }
+
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] =
+        &[("path X Y", "show the shortest path from the given position")];
+
+    fn repl_command(input: &Self::Input, command: &str, args: &[&str]) -> Result<(), String> {
+        if command != "path" {
+            return Err(format!("Unknown command {command}"));
+        }
+        let start = Point::new(arg(args, 0, "X")?, arg(args, 1, "Y")?);
+        if start.y >= input.len() || start.x >= input[start.y].len() {
+            return Err(format!("{start:?} is outside of the grid"));
+        }
+        let mut grid = input.clone();
+        let (_, end) = find_start_end(&mut grid);
+        match a_star(&grid, start, &end) {
+            Some(path) => {
+                print!("{}", render_path(&path, &grid));
+                println!("{} steps", path.len() - 1);
+            }
+            None => println!("No path to the end"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            prop::collection::vec("[a-zSE]".prop_map(|c| c.chars().next().unwrap() as usize), 1..20),
+            1..20,
+        ).prop_map(|mut rows| {
+            // the start and the end are required
+            rows.push(vec!['S' as usize, 'E' as usize]);
+            rows
+        })) {
+            assert_roundtrip::<Day12>(&input);
+        }
+    }
 }
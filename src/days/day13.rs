@@ -1,39 +1,30 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt::Display};
 
 use itertools::Itertools;
 use nom::{
     branch::alt,
-    character::complete::{char, line_ending, u8},
+    character::complete::{char, line_ending, u32},
     combinator::{cut, map},
     multi::{count, separated_list0},
     sequence::{preceded, terminated, tuple},
     IResult,
 };
+use rand::Rng;
 
 use crate::days::Day;
 
 /// Recursive enum for representing the packets
 #[derive(Debug, Clone)]
 pub enum PacketItem {
-    Int(u8),
+    Int(u32),
     List(Vec<PacketItem>),
 }
 
 impl Eq for PacketItem {}
 
 impl PartialOrd for PacketItem {
-    /// Check if two packets or packet items are in the right order (Ordering::Less)
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (self, other) {
-            (PacketItem::Int(a), PacketItem::Int(b)) => Some(a.cmp(b)),
-            (PacketItem::List(a), PacketItem::List(b)) => Some(a.cmp(b)),
-            (PacketItem::Int(_), PacketItem::List(_)) => {
-                Some(PacketItem::List(vec![self.clone()]).cmp(other))
-            }
-            (PacketItem::List(_), PacketItem::Int(_)) => {
-                Some(self.cmp(&PacketItem::List(vec![other.clone()])))
-            }
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -47,14 +38,34 @@ impl PartialEq for PacketItem {
 }
 
 impl Ord for PacketItem {
+    /// Check if two packets or packet items are in the right order (Ordering::Less)
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        match (self, other) {
+            (PacketItem::Int(a), PacketItem::Int(b)) => a.cmp(b),
+            (PacketItem::List(a), PacketItem::List(b)) => a.cmp(b),
+            (PacketItem::Int(_), PacketItem::List(_)) => {
+                PacketItem::List(vec![self.clone()]).cmp(other)
+            }
+            (PacketItem::List(_), PacketItem::Int(_)) => {
+                self.cmp(&PacketItem::List(vec![other.clone()]))
+            }
+        }
+    }
+}
+
+impl Display for PacketItem {
+    /// Write the packet back in its original notation
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{i}"),
+            Self::List(items) => write!(f, "[{}]", items.iter().join(",")),
+        }
     }
 }
 
 impl PacketItem {
     /// The dividiers are a list of list of a single int (2 or 6)
-    pub fn is_divider(&self, val: u8) -> bool {
+    pub fn is_divider(&self, val: u32) -> bool {
         match self {
             Self::Int(_) => false,
             Self::List(i) => {
@@ -78,7 +89,7 @@ impl PacketItem {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pair {
     pub first: PacketItem,
     pub second: PacketItem,
@@ -106,7 +117,21 @@ fn parse_list(input: &str) -> IResult<&str, Vec<PacketItem>> {
 
 /// Parse an item, either a list or an int (recursive)
 fn parse_item(input: &str) -> IResult<&str, PacketItem> {
-    alt((map(u8, PacketItem::Int), map(parse_list, PacketItem::List)))(input)
+    alt((map(u32, PacketItem::Int), map(parse_list, PacketItem::List)))(input)
+}
+
+/// Generate a random list, whose items are themselves lists up to a nesting depth of 4
+fn generate_packet<R: Rng>(rng: &mut R, depth: usize) -> PacketItem {
+    let items = (0..rng.gen_range(0..=5))
+        .map(|_| {
+            if depth < 4 && rng.gen_bool(0.3) {
+                generate_packet(rng, depth + 1)
+            } else {
+                PacketItem::Int(rng.gen_range(0..=10))
+            }
+        })
+        .collect();
+    PacketItem::List(items)
 }
 
 pub struct Day13;
@@ -124,6 +149,22 @@ impl Day for Day13 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|pair| format!("{}\n{}", pair.first, pair.second))
+            .join("\n\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (0..150 * scale)
+            .map(|_| Pair {
+                first: generate_packet(rng, 0),
+                second: generate_packet(rng, 0),
+            })
+            .collect()
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.051101ms
@@ -157,3 +198,29 @@ impl Day for Day13 {
         (first_div + 1) * (second_div + 1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn packet() -> impl Strategy<Value = PacketItem> {
+        let leaf = any::<u32>().prop_map(PacketItem::Int);
+        let item = leaf.prop_recursive(4, 32, 5, |inner| {
+            prop::collection::vec(inner, 0..5).prop_map(PacketItem::List)
+        });
+        prop::collection::vec(item, 0..5).prop_map(PacketItem::List)
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            (packet(), packet()).prop_map(|(first, second)| Pair { first, second }),
+            0..10,
+        )) {
+            assert_roundtrip::<Day13>(&input);
+        }
+    }
+}
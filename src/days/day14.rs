@@ -9,100 +9,102 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::{days::Day, geom};
 
-#[derive(Debug)]
-pub struct Point {
-    x: usize,
-    y: usize,
-}
+/// x points to the right, y points to the bottom
+type Point = geom::Point<usize>;
 
-impl Point {
-    fn can_move(&mut self, grid: &mut [Vec<bool>]) -> Option<bool> {
-        if self.y + 1 >= grid[self.x].len() {
-            return None; //  sand fell off
-        }
-        if !grid[self.x][self.y + 1] {
-            // cell below is free
-            self.y += 1;
-            return Some(true);
-        }
-        if self.x == 0 || self.x + 1 >= grid.len() {
-            return None; // sand fell off
-        }
-        if !grid[self.x - 1][self.y + 1] {
-            self.x -= 1;
-            self.y += 1;
-            return Some(true);
-        }
-        if !grid[self.x + 1][self.y + 1] {
-            self.x += 1;
-            self.y += 1;
-            return Some(true);
-        }
-        grid[self.x][self.y] = true;
-        Some(false)
+/// Move a grain of sand one step down if possible
+///
+/// Returns `Some(true)` if the sand moved, `Some(false)` if it came to rest and `None` if it fell off the grid.
+fn can_move(sand: &mut Point, grid: &mut [Vec<bool>]) -> Option<bool> {
+    if sand.y + 1 >= grid[sand.x].len() {
+        return None; //  sand fell off
+    }
+    if !grid[sand.x][sand.y + 1] {
+        // cell below is free
+        sand.y += 1;
+        return Some(true);
+    }
+    if sand.x == 0 || sand.x + 1 >= grid.len() {
+        return None; // sand fell off
+    }
+    if !grid[sand.x - 1][sand.y + 1] {
+        sand.x -= 1;
+        sand.y += 1;
+        return Some(true);
+    }
+    if !grid[sand.x + 1][sand.y + 1] {
+        sand.x += 1;
+        sand.y += 1;
+        return Some(true);
     }
+    grid[sand.x][sand.y] = true;
+    Some(false)
+}
 
-    fn can_move2(&mut self, grid: &mut VecDeque<Vec<bool>>, top_left: &mut Point) -> Option<bool> {
-        if self.y + 1 >= grid[self.x].len() {
-            grid[self.x][self.y] = true;
-            return Some(false); //  we hit the floor
-        }
-        if !grid[self.x][self.y + 1] {
-            // cell below is free
-            self.y += 1;
-            return Some(true);
-        }
-        if self.x == 0 {
-            // expand grid
-            grid.push_front(vec![false; grid[self.x].len()]);
-            self.x += 1; // shift to maintain relative position
-            top_left.x -= 1;
-        }
-        if self.x + 1 >= grid.len() {
-            // expand grid
-            grid.push_back(vec![false; grid[self.x].len()]);
-        }
-        if !grid[self.x - 1][self.y + 1] {
-            self.x -= 1;
-            self.y += 1;
-            return Some(true);
-        }
-        if !grid[self.x + 1][self.y + 1] {
-            self.x += 1;
-            self.y += 1;
-            return Some(true);
-        }
-        grid[self.x][self.y] = true;
-        if self.y == 0 {
-            return None;
-        }
-        Some(false)
+/// Move a grain of sand one step down if possible, with an infinite floor below the grid
+///
+/// Returns `Some(true)` if the sand moved, `Some(false)` if it came to rest and `None` if the source got blocked.
+fn can_move2(
+    sand: &mut Point,
+    grid: &mut VecDeque<Vec<bool>>,
+    top_left: &mut Point,
+) -> Option<bool> {
+    if sand.y + 1 >= grid[sand.x].len() {
+        grid[sand.x][sand.y] = true;
+        return Some(false); //  we hit the floor
+    }
+    if !grid[sand.x][sand.y + 1] {
+        // cell below is free
+        sand.y += 1;
+        return Some(true);
+    }
+    if sand.x == 0 {
+        // expand grid
+        grid.push_front(vec![false; grid[sand.x].len()]);
+        sand.x += 1; // shift to maintain relative position
+        top_left.x -= 1;
     }
+    if sand.x + 1 >= grid.len() {
+        // expand grid
+        grid.push_back(vec![false; grid[sand.x].len()]);
+    }
+    if !grid[sand.x - 1][sand.y + 1] {
+        sand.x -= 1;
+        sand.y += 1;
+        return Some(true);
+    }
+    if !grid[sand.x + 1][sand.y + 1] {
+        sand.x += 1;
+        sand.y += 1;
+        return Some(true);
+    }
+    grid[sand.x][sand.y] = true;
+    if sand.y == 0 {
+        return None;
+    }
+    Some(false)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct RockFormation {
     path: Vec<Point>,
 }
 
 fn grid_bounds(input: &[RockFormation]) -> (Point, Point) {
-    let MinMaxResult::MinMax(min_x, max_x) = input
-        .iter()
-        .flat_map(|p| &p.path)
-        .map(|p| p.x)
-        .minmax() else {
-            unreachable!();
-        };
-    let MinMaxResult::MinMax(min_y, max_y) = input
-        .iter()
-        .flat_map(|p| &p.path)
-        .map(|p| p.y)
-        .minmax() else {
-            unreachable!();
-        };
+    let MinMaxResult::MinMax(min_x, max_x) =
+        input.iter().flat_map(|p| &p.path).map(|p| p.x).minmax()
+    else {
+        unreachable!();
+    };
+    let MinMaxResult::MinMax(min_y, max_y) =
+        input.iter().flat_map(|p| &p.path).map(|p| p.y).minmax()
+    else {
+        unreachable!();
+    };
     (Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y })
 }
 
@@ -153,6 +155,80 @@ impl Day for Day14 {
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|rock| {
+                rock.path
+                    .iter()
+                    .map(|p| format!("{},{}", p.x, p.y))
+                    .join(" -> ")
+            })
+            .join("\n")
+    }
+
+    /// `scale` spreads more rock formations over a wider and deeper cave, up to the size that the solver can handle
+    /// with the sand source at x=500
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let half_width = (50 * scale).min(400);
+        let depth = (160 * scale).min(400);
+        let (min, max) = (
+            Point::new(500 - half_width, 13),
+            Point::new(500 + half_width, 13 + depth),
+        );
+        // a short ledge below the source makes sure the cave is wide enough for the sand to enter it
+        let ledge_y = rng.gen_range(min.y..=max.y);
+        let mut formations = vec![RockFormation {
+            path: vec![
+                Point::new(500 - rng.gen_range(0..=5), ledge_y),
+                Point::new(500 + rng.gen_range(0..=5), ledge_y),
+            ],
+        }];
+        formations.extend((1..150 * scale).map(|_| {
+            let mut point = Point::new(rng.gen_range(min.x..=max.x), rng.gen_range(min.y..=max.y));
+            let mut path = vec![point];
+            let horizontal = rng.gen_bool(0.5);
+            for i in 0..rng.gen_range(1..=5) {
+                let len = rng.gen_range(1..=8);
+                let forward = rng.gen_bool(0.5);
+                // alternate between horizontal and vertical segments
+                if (i % 2 == 0) == horizontal {
+                    point.x = if forward {
+                        (point.x + len).min(max.x)
+                    } else {
+                        (point.x - len).max(min.x)
+                    };
+                } else {
+                    point.y = if forward {
+                        (point.y + len).min(max.y)
+                    } else {
+                        (point.y - len).max(min.y)
+                    };
+                }
+                path.push(point);
+            }
+            RockFormation { path }
+        }));
+        formations
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let segments: usize = input
+            .iter()
+            .map(|rock| rock.path.len().saturating_sub(1))
+            .sum();
+        let mut stats = vec![
+            ("rock paths", input.len().to_string()),
+            ("rock segments", segments.to_string()),
+        ];
+        if !input.is_empty() {
+            let (min, max) = grid_bounds(input);
+            stats.push(("grid", format!("{}x{}", max.x - min.x + 1, max.y + 1)));
+            stats.push(("x range", format!("{}..={}", min.x, max.x)));
+        }
+        stats
+    }
+
     type Output1 = usize;
 
     fn part_1(input: &Self::Input) -> Self::Output1 {
@@ -169,7 +245,7 @@ impl Day for Day14 {
                 y: 0,
             };
             loop {
-                match sand.can_move(&mut grid) {
+                match can_move(&mut sand, &mut grid) {
                     Some(true) => {
                         continue; // sand is still moving down
                     }
@@ -207,7 +283,7 @@ impl Day for Day14 {
                 y: 0,
             };
             loop {
-                match sand.can_move2(&mut grid, &mut top_left) {
+                match can_move2(&mut sand, &mut grid, &mut top_left) {
                     Some(true) => {
                         continue; // sand is still moving down
                     }
@@ -227,3 +303,22 @@ impl Day for Day14 {
         sand_counter
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            prop::collection::vec(any::<(u32, u32)>().prop_map(|(x, y)| Point::new(x as usize, y as usize)), 1..6)
+                .prop_map(|path| RockFormation { path }),
+            1..10,
+        )) {
+            assert_roundtrip::<Day14>(&input);
+        }
+    }
+}
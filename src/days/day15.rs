@@ -1,5 +1,6 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     character::complete::{i64, line_ending},
@@ -8,25 +9,15 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 use rayon::prelude::*;
 
-use crate::days::Day;
+use crate::{days::Day, geom, repl::arg};
 
 /// x axis points to the right, y axis points to the bottom
-#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Debug)]
-pub struct Point {
-    x: isize,
-    y: isize,
-}
-
-impl Point {
-    /// Manhattan distance to another point
-    pub fn dist(&self, other: &Self) -> isize {
-        (self.x.max(other.x) - self.x.min(other.x)) + (self.y.max(other.y) - self.y.min(other.y))
-    }
-}
+type Point = geom::Point<isize>;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Device {
     Sensor(Point), // Point is position of closest beacon
     Beacon,
@@ -38,7 +29,7 @@ impl Device {
         let Self::Sensor(closest_beacon) = self else {
             unimplemented!("function only valid for sensors");
         };
-        self_pos.dist(closest_beacon)
+        self_pos.manhattan(closest_beacon)
     }
 }
 
@@ -76,6 +67,133 @@ fn ranges_with_no_beacons(input: &BTreeMap<Point, Device>, y: isize) -> Vec<(isi
     merged
 }
 
+/// Number of positions on row `y` where no untracked beacon can be present
+fn no_beacon_count(input: &BTreeMap<Point, Device>, y: isize) -> isize {
+    // get the ranges where no untracked beacons can be present
+    let ranges = ranges_with_no_beacons(input, y);
+    // get the number of positions where no untracked beacon can be present
+    let mut count = ranges
+        .iter()
+        .map(|(start, end)| *end - *start + 1)
+        .sum::<isize>();
+    // some tracked beacons might be present on line y, so we need to decrement 1 for each becon on this line
+    // in the returned range(s).
+    for (pos, device) in input {
+        // only consider Beacons
+        if matches!(device, Device::Sensor(_)) {
+            continue;
+        }
+        // only consider Beacons on the current line
+        if pos.y != y {
+            continue;
+        }
+        for (start, end) in ranges.iter() {
+            // in case the beacon is in a range where no untracked beacon can be, we decrement the count
+            if pos.x >= *start && pos.x <= *end {
+                count -= 1;
+            }
+        }
+    }
+    count
+}
+
+/// Check every position of row `y` which is within reach of a sensor
+fn no_beacon_count_reference(input: &BTreeMap<Point, Device>, y: isize) -> isize {
+    let sensors = input
+        .iter()
+        .filter_map(|(pos, device)| match device {
+            Device::Sensor(_) => Some((pos, device.closest_beacon_distance(pos))),
+            Device::Beacon => None,
+        })
+        .collect_vec();
+    let min_x = sensors
+        .iter()
+        .map(|(pos, dist)| pos.x - dist)
+        .min()
+        .unwrap();
+    let max_x = sensors
+        .iter()
+        .map(|(pos, dist)| pos.x + dist)
+        .max()
+        .unwrap();
+    (min_x..=max_x)
+        .filter(|&x| {
+            let p = Point::new(x, y);
+            !matches!(input.get(&p), Some(Device::Beacon))
+                && sensors.iter().any(|(pos, dist)| pos.manhattan(&p) <= *dist)
+        })
+        .count() as isize
+}
+
+/// Tuning frequency of the only position in the search area from 0 to `max` where an untracked beacon can be
+///
+/// Returns `None` if the sensors cover the whole search area.
+fn tuning_frequency(input: &BTreeMap<Point, Device>, max: isize) -> Option<isize> {
+    // scan all rows
+    (0..=max).into_par_iter().find_map_any(|y| {
+        // get all ranges where no untracked beacons can be
+        let ranges = ranges_with_no_beacons(input, y);
+        // in case there are more than 1 ranges, it means there is an interval in-between where an untracked
+        // beacon could be. Since only 1 position for the untracked beacon is possible, it has to be one above
+        // the upper bound of the first range.
+        if ranges.len() > 1 {
+            let x = ranges[0].1 + 1;
+            // calculate the tuning frequency
+            return Some(x * 4_000_000 + y);
+        }
+        None
+    })
+}
+
+/// Check every position of the search area from 0 to `max`
+fn tuning_frequency_reference(input: &BTreeMap<Point, Device>, max: isize) -> Option<isize> {
+    let (x, y) = (0..=max).cartesian_product(0..=max).find(|&(x, y)| {
+        let p = Point::new(x, y);
+        input.iter().all(|(pos, device)| match device {
+            Device::Sensor(_) => pos.manhattan(&p) > device.closest_beacon_distance(pos),
+            Device::Beacon => *pos != p,
+        })
+    })?;
+    Some(x * 4_000_000 + y)
+}
+
+/// Sensors on a grid have overlapping areas which cover everything from 0 to `max` but the position of the distress
+/// beacon. There are `lines` sensors along each axis.
+fn generate_devices<R: Rng>(rng: &mut R, max: isize, lines: isize) -> BTreeMap<Point, Device> {
+    let step = max / lines;
+    let gap = Point::new(rng.gen_range(0..=max), rng.gen_range(0..=max));
+    // four sensors placed diagonally from the gap cover all its surroundings, stopping just before it
+    let k = 2 * step + 3;
+    let mut sensors = [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+        .map(|(dx, dy)| (Point::new(gap.x + dx * k, gap.y + dy * k), 2 * k - 1))
+        .to_vec();
+    // the grid covers the whole search area, and the sensors close to the gap are shrunk so they don't reach it
+    let offset = Point::new(rng.gen_range(0..step), rng.gen_range(0..step));
+    for (i, j) in (-2..=lines + 1).cartesian_product(-2..=lines + 1) {
+        let pos = Point::new(offset.x + i * step, offset.y + j * step);
+        let radius = (step + 1).min(pos.manhattan(&gap) - 1);
+        if radius >= 1 && sensors.iter().all(|(p, _)| *p != pos) {
+            sensors.push((pos, radius));
+        }
+    }
+    let positions = sensors.iter().map(|(pos, _)| *pos).collect::<BTreeSet<_>>();
+    let mut devices = BTreeMap::new();
+    for (pos, radius) in sensors {
+        // the closest beacon is anywhere on the edge of the sensor's area
+        let beacon = loop {
+            let dx = rng.gen_range(-radius..=radius);
+            let dy = (radius - dx.abs()) * if rng.gen_bool(0.5) { 1 } else { -1 };
+            let beacon = Point::new(pos.x + dx, pos.y + dy);
+            if !positions.contains(&beacon) {
+                break beacon;
+            }
+        };
+        devices.insert(beacon, Device::Beacon);
+        devices.insert(pos, Device::Sensor(beacon));
+    }
+    devices
+}
+
 pub struct Day15;
 
 impl Day for Day15 {
@@ -112,70 +230,121 @@ impl Day for Day15 {
         let mut devices = BTreeMap::new();
         for (pos, s) in sensors {
             if let Device::Sensor(b) = &s {
-                devices.insert(b.clone(), Device::Beacon);
+                devices.insert(*b, Device::Beacon);
             }
-            devices.insert(pos.clone(), s);
+            devices.insert(pos, s);
         }
         Ok((rest, devices))
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .filter_map(|(pos, device)| match device {
+                Device::Sensor(beacon) => Some(format!(
+                    "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                    pos.x, pos.y, beacon.x, beacon.y
+                )),
+                Device::Beacon => None,
+            })
+            .join("\n")
+    }
+
+    /// `scale` multiplies the number of sensors along each axis
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        generate_devices(rng, 4_000_000, 4 * scale as isize)
+    }
+
     type Output1 = isize;
 
     /// Part 1 took 0.006ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
-        let y: isize = 2_000_000;
-        // get the ranges where no untracked beacons can be present
-        let ranges = ranges_with_no_beacons(input, y);
-        println!("{ranges:?}");
-        // get the number of positions where no untracked beacon can be present
-        let mut count = ranges
-            .iter()
-            .map(|(start, end)| *end - *start + 1)
-            .sum::<isize>();
-        // some tracked beacons might be present on line y = 2M, so we need to decrement 1 for each becon on this line
-        // in the returned range(s).
-        for (pos, device) in input {
-            // only consider Beacons
-            if matches!(device, Device::Sensor(_)) {
-                continue;
-            }
-            // only consider Beacons on the current line
-            if pos.y != y {
-                continue;
-            }
-            for (start, end) in ranges.iter() {
-                // in case the beacon is in a range where no untracked beacon can be, we decrement the count
-                if pos.x >= *start && pos.x <= *end {
-                    count -= 1;
-                }
-            }
-        }
-        count
+        no_beacon_count(input, 2_000_000)
     }
 
     type Output2 = isize;
 
     /// Part 2 took 12.1516ms
     fn part_2(input: &Self::Input) -> Self::Output2 {
-        // scan all rows
-        let Some(Some(answer)) = (0..=4_000_000)
-            .into_par_iter()
-            .map(|y| {
-                // get all ranges where no untracked beacons can be
-                let ranges = ranges_with_no_beacons(input, y);
-                // in case there are more than 1 ranges, it means there is an interval in-between where an untracked
-                // beacon could be. Since only 1 position for the untracked beacon is possible, it has to be one above
-                // the upper bound of the first range.
-                if ranges.len() > 1 {
-                    let x = ranges[0].1 + 1;
-                    // calculate the tuning frequency
-                    return Some(x * 4_000_000 + y);
-                }
-                None
-            })
-            .find_any(|v| v.is_some()) else {
-                panic!("not found");
-            };
-        answer
+        tuning_frequency(input, 4_000_000).expect("not found")
+    }
+
+    /// The range merging is compared with checking every position of a small search area one by one
+    fn differential<R: Rng>(rng: &mut R) -> Option<(Self::Input, Vec<String>)> {
+        let max = rng.gen_range(10..=60);
+        let input = generate_devices(rng, max, 2);
+        let y = rng.gen_range(0..=max);
+        let mut disagreements = Vec::new();
+        let (fast, reference) = (
+            no_beacon_count(&input, y),
+            no_beacon_count_reference(&input, y),
+        );
+        if fast != reference {
+            disagreements.push(format!(
+                "part 1 on row {y}: got {fast}, reference gave {reference}"
+            ));
+        }
+        let (fast, reference) = (
+            tuning_frequency(&input, max),
+            tuning_frequency_reference(&input, max),
+        );
+        if fast != reference {
+            disagreements.push(format!(
+                "part 2 with a search area up to {max}: got {fast:?}, reference gave {reference:?}"
+            ));
+        }
+        Some((input, disagreements))
+    }
+
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] = &[
+        (
+            "row Y",
+            "count the positions where no beacon can be on the given row",
+        ),
+        (
+            "area MAX",
+            "find the distress beacon in a search area from 0 to MAX",
+        ),
+    ];
+
+    fn repl_command(input: &Self::Input, command: &str, args: &[&str]) -> Result<(), String> {
+        match command {
+            "row" => println!("{}", no_beacon_count(input, arg(args, 0, "Y")?)),
+            "area" => {
+                let max = arg(args, 0, "MAX")?;
+                let frequency = tuning_frequency(input, max)
+                    .ok_or(format!("No free position found from 0 to {max}"))?;
+                println!("{frequency}");
+            }
+            _ => return Err(format!("Unknown command {command}")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn point() -> impl Strategy<Value = Point> {
+        any::<(i32, i32)>().prop_map(|(x, y)| Point::new(x as isize, y as isize))
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(sensors in prop::collection::btree_map(point(), point(), 0..10)
+            // a beacon can't be at the same position as a sensor
+            .prop_filter("beacon on a sensor", |s| s.values().all(|b| !s.contains_key(b))))
+        {
+            let mut input = BTreeMap::new();
+            for (pos, beacon) in sensors {
+                input.insert(beacon, Device::Beacon);
+                input.insert(pos, Device::Sensor(beacon));
+            }
+            assert_roundtrip::<Day15>(&input);
+        }
     }
 }
@@ -11,8 +11,12 @@ use nom::{
     IResult,
 };
 use petgraph::{algo::floyd_warshall, prelude::*};
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
 
-use crate::days::Day;
+use crate::{days::Day, repl::arg};
 
 #[derive(Debug)]
 pub struct Data {
@@ -77,6 +81,234 @@ fn max_total_released(
     max_value
 }
 
+/// Try every possible action for each actor, minute by minute, remembering the best release from each state
+///
+/// Each minute, an actor can move to a neighboring valve, open the valve it stands at, or wait.
+fn max_released_reference(
+    graph: &UnGraph<usize, u8>,
+    positions: Vec<NodeIndex>,
+    opened: u64,
+    remaining_time: isize,
+    cache: &mut HashMap<(Vec<NodeIndex>, u64, isize), isize>,
+) -> isize {
+    if remaining_time <= 0 {
+        return 0;
+    }
+    let key = (positions.clone(), opened, remaining_time);
+    if let Some(&released) = cache.get(&key) {
+        return released;
+    }
+    let mut max_value = 0;
+    // `None` means opening the valve, `Some` means going to (or staying at) a valve
+    let all_actions = positions
+        .iter()
+        .map(|&pos| {
+            let mut actions = graph.neighbors(pos).unique().map(Some).collect_vec();
+            actions.push(Some(pos));
+            if graph[pos] > 0 && opened & 1 << pos.index() == 0 {
+                actions.push(None);
+            }
+            actions
+        })
+        .multi_cartesian_product();
+    for actions in all_actions {
+        let mut next_opened = opened;
+        let mut released = 0;
+        let mut next_positions = Vec::with_capacity(positions.len());
+        for (&pos, action) in positions.iter().zip(actions) {
+            match action {
+                Some(next) => next_positions.push(next),
+                None => {
+                    // two actors at the same valve can't both open it
+                    if next_opened & 1 << pos.index() == 0 {
+                        released += graph[pos] as isize * (remaining_time - 1);
+                        next_opened |= 1 << pos.index();
+                    }
+                    next_positions.push(pos);
+                }
+            }
+        }
+        released += max_released_reference(
+            graph,
+            next_positions,
+            next_opened,
+            remaining_time - 1,
+            cache,
+        );
+        max_value = max_value.max(released);
+    }
+    cache.insert(key, max_value);
+    max_value
+}
+
+/// Build the graph from the parsed valves, given as (name, flow rate, connected valves)
+fn build_data(valves: &[(&str, usize, Vec<&str>)]) -> Data {
+    let mut graph = UnGraph::new_undirected();
+    let mut valves_map = HashMap::new();
+    let mut valves_by_index = HashMap::new();
+    let mut first_valve: Option<NodeIndex> = None;
+    for valve in valves {
+        let node = graph.add_node(valve.1);
+        valves_map.insert(valve.0.to_string(), node);
+        valves_by_index.insert(node, valve.0.to_string());
+        if valve.0 == "AA" {
+            first_valve = Some(node);
+        }
+    }
+    for valve in valves {
+        for &edge in &valve.2 {
+            let this = valves_map.get(valve.0).unwrap();
+            let other = valves_map.get(edge).unwrap();
+            graph.add_edge(*this, *other, 1); // weight is always 1 = 1 min
+        }
+    }
+    Data {
+        graph,
+        valves: valves_map,
+        valves_by_index,
+        start: first_valve.unwrap(),
+    }
+}
+
+/// Generate `count` connected valves, `with_flow` of which have a non-zero flow rate
+///
+/// There must be at least 3 valves, otherwise the loops can't be added.
+fn generate_data<R: Rng>(rng: &mut R, count: usize, with_flow: usize) -> Data {
+    let mut names = vec!["AA".to_string()];
+    while names.len() < count {
+        let name: String = (0..2).map(|_| rng.gen_range('A'..='Z')).collect();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    // the starting valve never has any flow
+    let mut flows = vec![0; count];
+    for i in sample(rng, count - 1, with_flow) {
+        flows[i + 1] = rng.gen_range(3..=25);
+    }
+    // a random tree makes sure that every valve can be reached, then a few more tunnels create loops
+    let mut tunnels = (1..count).map(|i| (rng.gen_range(0..i), i)).collect_vec();
+    while tunnels.len() < count + count / 4 {
+        let (a, b) = (rng.gen_range(0..count), rng.gen_range(0..count));
+        if a != b && !tunnels.contains(&(a.min(b), a.max(b))) {
+            tunnels.push((a.min(b), a.max(b)));
+        }
+    }
+    let mut order = (0..count).collect_vec();
+    order.shuffle(rng);
+    let valves = order
+        .into_iter()
+        .map(|i| {
+            let connected = tunnels
+                .iter()
+                .filter_map(|&(a, b)| {
+                    if a == i {
+                        Some(names[b].as_str())
+                    } else if b == i {
+                        Some(names[a].as_str())
+                    } else {
+                        None
+                    }
+                })
+                .collect_vec();
+            (names[i].as_str(), flows[i], connected)
+        })
+        .collect_vec();
+    build_data(&valves)
+}
+
+impl PartialEq for Data {
+    /// Two inputs are the same if they have the same valves with the same flow rates and tunnels
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+            && self.valves_by_index == other.valves_by_index
+            && self.graph.node_count() == other.graph.node_count()
+            && self.graph.node_indices().all(|node| {
+                self.graph[node] == other.graph[node]
+                    && self
+                        .graph
+                        .neighbors(node)
+                        .sorted()
+                        .eq(other.graph.neighbors(node).sorted())
+            })
+    }
+}
+
+/// Maximum pressure released in `time` minutes when opening the valves alone
+fn released_alone(input: &Data, time: isize) -> isize {
+    // Get a map of the shortest distance from any node to any other node in the graph
+    let dist = floyd_warshall(&input.graph, |_| 1).unwrap();
+    // Filter the valves that have a non-zero flow
+    let nonzero_valves = input
+        .graph
+        .node_indices()
+        .filter(|&n| {
+            // consider unopened valves with a non-zero flow
+            *input.graph.node_weight(n).unwrap() > 0
+        })
+        .collect_vec();
+    // Get the maximum possible pressure release
+    max_total_released(
+        &input.graph,
+        input.start,
+        time,
+        &nonzero_valves,
+        BitArray::ZERO,
+        &dist,
+    )
+}
+
+/// Maximum pressure released in `time` minutes when opening the valves with the help of an elephant
+fn released_with_elephant(input: &Data, time: isize) -> isize {
+    let mut max_value = 0;
+    let dist = floyd_warshall(&input.graph, |_| 1).unwrap();
+    let nonzero_valves = input
+        .graph
+        .node_indices()
+        .filter(|&n| {
+            // consider unopened valves with a non-zero flow
+            *input.graph.node_weight(n).unwrap() > 0
+        })
+        .collect_vec();
+    // We can distribute the nodes to visit unevenly (e.g. 1 for me, 14 for elephant) all the way until 7 and 8.
+    // With a single valve, the best is to leave it all to the elephant, so we also consider 0 for me.
+    for i in 0..=nonzero_valves.len() / 2 {
+        // Get all the combinations of nodes possible if I choose k valves for my trip
+        for my_valves in nonzero_valves.iter().cloned().combinations(i) {
+            // The remaining nodes will be visited by the elephant
+            let elephant_valves = nonzero_valves
+                .iter()
+                .cloned()
+                .filter(|v| !my_valves.contains(v))
+                .collect_vec();
+
+            // Get the release from my nodes
+            let max_mine = max_total_released(
+                &input.graph,
+                input.start,
+                time,
+                &my_valves,
+                BitArray::ZERO,
+                &dist,
+            );
+            // Get the release from the elephant's nodes
+            let max_elephant = max_total_released(
+                &input.graph,
+                input.start,
+                time,
+                &elephant_valves,
+                BitArray::ZERO,
+                &dist,
+            );
+            // In case we found a new best combination, we save its value
+            if max_mine + max_elephant > max_value {
+                max_value = max_mine + max_elephant;
+            }
+        }
+    }
+    max_value
+}
+
 pub struct Day16;
 
 impl Day for Day16 {
@@ -103,109 +335,191 @@ impl Day for Day16 {
                 |(_, id, _, flow, _, _, _, _, _, _, _, edges)| (id, flow, edges),
             ),
         )(input)?;
-        let mut graph = UnGraph::new_undirected();
-        let mut valves_map = HashMap::new();
-        let mut valves_by_index = HashMap::new();
-        let mut first_valve: Option<NodeIndex> = None;
-        for valve in valves.iter() {
-            let node = graph.add_node(valve.1);
-            valves_map.insert(valve.0.to_string(), node);
-            valves_by_index.insert(node, valve.0.to_string());
-            if valve.0 == "AA" {
-                first_valve = Some(node);
-            }
-        }
-        for valve in &valves {
-            for &edge in &valve.2 {
-                let this = valves_map.get(valve.0).unwrap();
-                let other = valves_map.get(edge).unwrap();
-                graph.add_edge(*this, *other, 1); // weight is always 1 = 1 min
-            }
-        }
-        let data = Data {
-            graph,
-            valves: valves_map,
-            valves_by_index,
-            start: first_valve.unwrap(),
-        };
-        Ok((rest, data))
+        Ok((rest, build_data(&valves)))
+    }
+
+    fn unparse(input: &Self::Input) -> String {
+        let graph = &input.graph;
+        graph
+            .node_indices()
+            .map(|node| {
+                // each tunnel was added from both ends, so we need to deduplicate the neighbors
+                let tunnels = graph
+                    .neighbors(node)
+                    .unique()
+                    .map(|n| input.valves_by_index[&n].as_str())
+                    .sorted()
+                    .collect_vec();
+                let tunnels_text = match tunnels.len() {
+                    1 => format!("tunnel leads to valve {}", tunnels[0]),
+                    _ => format!("tunnels lead to valves {}", tunnels.join(", ")),
+                };
+                format!(
+                    "Valve {} has flow rate={}; {tunnels_text}",
+                    input.valves_by_index[&node], graph[node]
+                )
+            })
+            .join("\n")
+    }
+
+    /// `scale` only adds valves without any flow, because the solvers are exponential in the number of valves with a
+    /// flow, and all the valves must fit in a 64-bit set
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        generate_data(rng, (40 + 10 * scale).min(64), 15)
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let non_zero = input.graph.node_weights().filter(|&&flow| flow > 0).count();
+        vec![
+            ("valves", input.graph.node_count().to_string()),
+            ("non-zero valves", non_zero.to_string()),
+            ("tunnels", input.graph.edge_count().to_string()),
+        ]
     }
 
     type Output1 = isize;
 
     /// Part 1 took 42.9ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
-        // Get a map of the shortest distance from any node to any other node in the graph
-        let dist = floyd_warshall(&input.graph, |_| 1).unwrap();
-        // Filter the valves that have a non-zero flow
-        let nonzero_valves = input
-            .graph
-            .node_indices()
-            .filter(|&n| {
-                // consider unopened valves with a non-zero flow
-                *input.graph.node_weight(n).unwrap() > 0
-            })
-            .collect_vec();
-        // Get the maximum possible pressure release
-        max_total_released(
-            &input.graph,
-            input.start,
-            30,
-            &nonzero_valves,
-            BitArray::ZERO,
-            &dist,
-        )
+        released_alone(input, 30)
     }
 
     type Output2 = isize;
 
     /// Part 2 took 7.084s
     fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut max_value = 0;
-        let dist = floyd_warshall(&input.graph, |_| 1).unwrap();
-        let nonzero_valves = input
-            .graph
-            .node_indices()
-            .filter(|&n| {
-                // consider unopened valves with a non-zero flow
-                *input.graph.node_weight(n).unwrap() > 0
-            })
-            .collect_vec();
-        // We can distribute the nodes to visit unevenly (e.g. 1 for me, 14 for elephant) all the way until 7 and 8
-        for i in 1..=nonzero_valves.len() / 2 {
-            // Get all the combinations of nodes possible if I choose k valves for my trip
-            for my_valves in nonzero_valves.iter().cloned().combinations(i) {
-                // The remaining nodes will be visited by the elephant
-                let elephant_valves = nonzero_valves
-                    .iter()
-                    .cloned()
-                    .filter(|v| !my_valves.contains(v))
-                    .collect_vec();
+        released_with_elephant(input, 26)
+    }
 
-                // Get the release from my nodes
-                let max_mine = max_total_released(
-                    &input.graph,
-                    input.start,
-                    26,
-                    &my_valves,
-                    BitArray::ZERO,
-                    &dist,
-                );
-                // Get the release from the elephant's nodes
-                let max_elephant = max_total_released(
-                    &input.graph,
-                    input.start,
-                    26,
-                    &elephant_valves,
-                    BitArray::ZERO,
-                    &dist,
+    /// Splitting the valves between me and the elephant is compared with moving both of us at the same time, minute by
+    /// minute
+    fn differential<R: Rng>(rng: &mut R) -> Option<(Self::Input, Vec<String>)> {
+        let count = rng.gen_range(3..=8);
+        let with_flow = rng.gen_range(0..count.min(6));
+        let input = generate_data(rng, count, with_flow);
+        let mut disagreements = Vec::new();
+        let (fast, reference) = (
+            Self::part_1(&input),
+            max_released_reference(&input.graph, vec![input.start], 0, 30, &mut HashMap::new()),
+        );
+        if fast != reference {
+            disagreements.push(format!("part 1: got {fast}, reference gave {reference}"));
+        }
+        let (fast, reference) = (
+            Self::part_2(&input),
+            max_released_reference(
+                &input.graph,
+                vec![input.start; 2],
+                0,
+                26,
+                &mut HashMap::new(),
+            ),
+        );
+        if fast != reference {
+            disagreements.push(format!("part 2: got {fast}, reference gave {reference}"));
+        }
+        Some((input, disagreements))
+    }
+
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] = &[
+        ("alone MINUTES", "pressure released alone in the given time"),
+        (
+            "together MINUTES",
+            "pressure released with the elephant in the given time",
+        ),
+        (
+            "valve NAME",
+            "flow rate of a valve and the valves it leads to",
+        ),
+    ];
+
+    fn repl_command(input: &Self::Input, command: &str, args: &[&str]) -> Result<(), String> {
+        match command {
+            "alone" => println!(
+                "Released {}",
+                released_alone(input, arg(args, 0, "MINUTES")?)
+            ),
+            "together" => println!(
+                "Released {}",
+                released_with_elephant(input, arg(args, 0, "MINUTES")?)
+            ),
+            "valve" => {
+                let name: String = arg(args, 0, "NAME")?;
+                let node = *input
+                    .valves
+                    .get(&name)
+                    .ok_or(format!("Unknown valve {name}"))?;
+                let tunnels = input
+                    .graph
+                    .neighbors(node)
+                    .unique()
+                    .map(|n| input.valves_by_index[&n].as_str())
+                    .sorted()
+                    .join(", ");
+                println!(
+                    "Valve {name} has flow rate {} and leads to {tunnels}",
+                    input.graph[node]
                 );
-                // In case we found a new best combination, we save its value
-                if max_mine + max_elephant > max_value {
-                    max_value = max_mine + max_elephant;
-                }
             }
+            _ => return Err(format!("Unknown command {command}")),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    /// With a single valve to open, the elephant can open it while I stay put
+    #[test]
+    fn elephant_alone() {
+        let (_, input) = Day16::parse(
+            "Valve AA has flow rate=0; tunnel leads to valve BB\nValve BB has flow rate=10; tunnel leads to valve AA",
+        )
+        .unwrap();
+        // one minute to walk to the valve and one to open it
+        assert_eq!(released_with_elephant(&input, 26), 10 * 24);
+    }
+
+    /// Valve names and flow rates, with symmetric tunnels between them
+    fn valves() -> impl Strategy<Value = Vec<(String, usize, Vec<String>)>> {
+        prop::collection::btree_map("[A-Z]{2}", 0..30usize, 0..10)
+            .prop_flat_map(|valves| {
+                let mut names = valves.into_iter().collect_vec();
+                names.push(("AA".to_string(), 0)); // the starting valve is always present
+                names.sort();
+                names.dedup_by(|a, b| a.0 == b.0);
+                let n = names.len();
+                (Just(names), prop::collection::vec(any::<bool>(), n * n))
+            })
+            .prop_map(|(names, adjacency)| {
+                let n = names.len();
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (name, flow))| {
+                        let tunnels = (0..n)
+                            .filter(|&j| i != j && adjacency[i.min(j) * n + i.max(j)])
+                            .map(|j| names[j].0.clone())
+                            .collect();
+                        (name.clone(), *flow, tunnels)
+                    })
+                    .collect()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(valves in valves()) {
+            let valves = valves
+                .iter()
+                .map(|(name, flow, tunnels)| (name.as_str(), *flow, tunnels.iter().map(String::as_str).collect()))
+                .collect_vec();
+            assert_roundtrip::<Day16>(&build_data(&valves));
         }
-        max_value
     }
 }
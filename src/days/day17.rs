@@ -4,11 +4,12 @@ use itertools::{
     FoldWhile::{Continue, Done},
     Itertools,
 };
-use nom::{character::complete::anychar, combinator::map, multi::many1, IResult};
+use nom::{branch::alt, character::complete::char, combinator::value, multi::many1, IResult};
+use rand::Rng;
 
-use crate::days::Day;
+use crate::{days::Day, repl::arg};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Push {
     Left,
     Right,
@@ -131,143 +132,244 @@ struct Identifier {
     grid: [u8; 64],
 }
 
+/// Height of the stack after dropping `pieces` pieces, simulating every one of them
+fn stack_height(input: &[Push], pieces: usize) -> usize {
+    // we make an infinite iterator for the air pushing actions
+    let mut push = input.iter().cycle();
+    // we create a grid that should hold the full stack, drawing the wall on the left (1 means occupied)
+    let mut grid = vec![0b1000_0000_u8; 4 * pieces + 8];
+    // at index 0 is the grid floor
+    grid[0] = 0b1111_1111;
+    let mut highest_z = 0_usize; // keep track of the stack height
+
+    // consider all the pieces
+    for i in 0..pieces {
+        let kind = (i % 5) as u8; // we cycle through the piece types
+        let mut piece = Piece::new(kind, highest_z + 4); // the piece is initially 4 above the top of the stack
+
+        // we loop until the part stops moving
+        loop {
+            // apply the sideways push from the air
+            match push.next() {
+                Some(Push::Left) => {
+                    piece.move_left(&grid);
+                }
+                Some(Push::Right) => {
+                    piece.move_right(&grid);
+                }
+                None => {
+                    unreachable!("we cycle through the iterator indefinitely");
+                }
+            }
+            // part cannot move down, we can save current position into the grid (exit loop)
+            if !piece.can_move_down(&grid) {
+                break;
+            }
+            // part can move down, we proceed
+            piece.move_down();
+        }
+        // update the highest position of the stack
+        highest_z = piece.get_highest_z().max(highest_z);
+        // mutate grid, adding our piece
+        for (j, piece_row) in piece.data.into_iter().enumerate() {
+            grid[piece.z + j] |= piece_row;
+        }
+    }
+    highest_z
+}
+
+/// Height of the stack after dropping `pieces` pieces, fast-forwarding once the stack starts repeating itself
+fn stack_height_with_cycles(input: &[Push], pieces: usize) -> usize {
+    // We want to be able to know where in the input sequence lies the current air push, hence enumerate()
+    // before the cycle() (so the index also cycles)
+    let mut push = input.iter().enumerate().cycle();
+    let mut skipped = 0; // this variable will keep track of how much height we fast-forwarded
+
+    // Init our grid like for the simple simulation, but a bit taller so we have time to notice a repeating pattern
+    let mut grid = [0b1000_0000_u8; 100_000];
+    grid[0] = 0b1111_1111;
+    let mut highest_z = 0_usize;
+    // Keep a cache of the (stack height, piece index) for a given combination of:
+    //  - current piece type
+    //  - air push index in the input sequence
+    //  - and state of the last few top rows in the grid (64)
+    // If we get a cache hit, it means we are in a repeating pattern and we can extract the period
+    let mut cache = HashMap::<Identifier, (usize, usize)>::new();
+    let mut i = 0;
+    // We should iterate over all the pieces
+    while i < pieces {
+        let kind = (i % 5) as u8;
+        let mut piece = Piece::new(kind, highest_z + 4);
+        let mut last_push_idx; // this will be used for the cache key
+        loop {
+            let (push_idx, push_type) = push.next().unwrap(); // get the next air push in the sequence (and its idx)
+            last_push_idx = push_idx; // save it in the parent scope
+            match push_type {
+                Push::Left => {
+                    piece.move_left(&grid);
+                }
+                Push::Right => {
+                    piece.move_right(&grid);
+                }
+            }
+            if !piece.can_move_down(&grid) {
+                break;
+            }
+            piece.move_down();
+        }
+        highest_z = piece.get_highest_z().max(highest_z);
+        for (j, piece_row) in piece.data.into_iter().enumerate() {
+            grid[piece.z + j] |= piece_row;
+        }
+
+        // Now we have finished our processing for this piece.
+        // Let's save the state in the cache (only when we have enough grid height to save, i.e. 64 rows).
+        // We only need to do this once, so when skipped > 0, we will not need to store cache anymore.
+        if highest_z > 64 && skipped == 0 {
+            // The identifier is the state of the system, which will repeat itself eventually.
+            // We save the pattern of the to 64 rows in the grid
+            let identifier = Identifier {
+                piece_kind: kind,
+                push_idx: last_push_idx,
+                grid: grid[highest_z - 63..=highest_z]
+                    .try_into()
+                    .expect("slice with incorrect length"),
+            };
+            if let Some((prev_height, prev_piece_idx)) = cache.insert(identifier, (highest_z, i)) {
+                // We got a cache hit! Let's compare the two states to know the period.
+                let height_diff = highest_z - prev_height;
+                let piece_diff = i - prev_piece_idx;
+                // Now we know how many periods (cycles) we can skip without affecting the state/repetition, among the
+                // pieces remaining after this one
+                let skip_repeats = (pieces - i - 1) / piece_diff;
+                let skip_pieces = skip_repeats * piece_diff;
+                let skip_height = skip_repeats * height_diff;
+                // We fast-forward our piece counter
+                i += skip_pieces;
+                // We record how much height we skipped, which we will add to the final result to get the real value
+                skipped = skip_height;
+            }
+        }
+        i += 1;
+    }
+    highest_z + skipped
+}
+
 pub struct Day17;
 
 impl Day for Day17 {
     type Input = Vec<Push>;
 
     fn parse(input: &str) -> IResult<&str, Self::Input> {
-        many1(map(anychar, |c| match c {
-            '<' => Push::Left,
-            _ => Push::Right,
-        }))(input)
+        many1(alt((
+            value(Push::Left, char('<')),
+            value(Push::Right, char('>')),
+        )))(input)
+    }
+
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|push| match push {
+                Push::Left => '<',
+                Push::Right => '>',
+            })
+            .collect()
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (0..10_091 * scale)
+            .map(|_| {
+                if rng.gen_bool(0.5) {
+                    Push::Left
+                } else {
+                    Push::Right
+                }
+            })
+            .collect()
     }
 
     type Output1 = usize;
 
     /// Part 1 took 0.1092ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
-        // we make an infinite iterator for the air pushing actions
-        let mut push = input.iter().cycle();
-        // we create a grid that should hold the full stack, drawing the wall on the left (1 means occupied)
-        let mut grid = [0b1000_0000_u8; 10_000];
-        // at index 0 is the grid floor
-        grid[0] = 0b1111_1111;
-        let mut highest_z = 0_usize; // keep track of the stack height
-
-        // consider 2022 pieces
-        for i in 0..2022 {
-            let kind = (i % 5) as u8; // we cycle through the piece types
-            let mut piece = Piece::new(kind, highest_z + 4); // the piece is initially 4 above the top of the stack
-
-            // we loop until the part stops moving
-            loop {
-                // apply the sideways push from the air
-                match push.next() {
-                    Some(Push::Left) => {
-                        piece.move_left(&grid);
-                    }
-                    Some(Push::Right) => {
-                        piece.move_right(&grid);
-                    }
-                    None => {
-                        unreachable!("we cycle through the iterator indefinitely");
-                    }
-                }
-                // part cannot move down, we can save current position into the grid (exit loop)
-                if !piece.can_move_down(&grid) {
-                    break;
-                }
-                // part can move down, we proceed
-                piece.move_down();
-            }
-            // update the highest position of the stack
-            highest_z = piece.get_highest_z().max(highest_z);
-            // mutate grid, adding our piece
-            for (j, piece_row) in piece.data.into_iter().enumerate() {
-                grid[piece.z + j] |= piece_row;
-            }
-        }
-        highest_z
+        stack_height(input, 2022)
     }
 
     type Output2 = usize;
 
     /// Part 2 took 0.562ms
     fn part_2(input: &Self::Input) -> Self::Output2 {
-        // We want to be able to know where in the input sequence lies the current air push, hence enumerate()
-        // before the cycle() (so the index also cycles)
-        let mut push = input.iter().enumerate().cycle();
-        let mut skipped = 0; // this variable will keep track of how much height we fast-forwarded
-
-        // Init our grid like last time, but a bit taller so we have time to notice a repeating pattern
-        let mut grid = [0b1000_0000_u8; 100_000];
-        grid[0] = 0b1111_1111;
-        let mut highest_z = 0_usize;
-        // Keep a cache of the (stack height, piece index) for a given combination of:
-        //  - current piece type
-        //  - air push index in the input sequence
-        //  - and state of the last few top rows in the grid (64)
-        // If we get a cache hit, it means we are in a repeating pattern and we can extract the period
-        let mut cache = HashMap::<Identifier, (usize, usize)>::new();
-        let mut i = 0;
-        // We should iterate over 1e12 pieces
-        while i < 1_000_000_000_000_usize {
-            let kind = (i % 5) as u8;
-            let mut piece = Piece::new(kind, highest_z + 4);
-            let mut last_push_idx; // this will be used for the cache key
-            loop {
-                let (push_idx, push_type) = push.next().unwrap(); // get the next air push in the sequence (and its idx)
-                last_push_idx = push_idx; // save it in the parent scope
-                match push_type {
-                    Push::Left => {
-                        piece.move_left(&grid);
-                    }
-                    Push::Right => {
-                        piece.move_right(&grid);
-                    }
-                }
-                if !piece.can_move_down(&grid) {
-                    break;
-                }
-                piece.move_down();
-            }
-            highest_z = piece.get_highest_z().max(highest_z);
-            for (j, piece_row) in piece.data.into_iter().enumerate() {
-                grid[piece.z + j] |= piece_row;
-            }
+        stack_height_with_cycles(input, 1_000_000_000_000)
+    }
 
-            // Now we have finished our processing for this piece.
-            // Let's save the state in the cache (only when we have enough grid height to save, i.e. 64 rows).
-            // We only need to do this once, so when skipped > 0, we will not need to store cache anymore.
-            if highest_z > 64 && skipped == 0 {
-                // The identifier is the state of the system, which will repeat itself eventually.
-                // We save the pattern of the to 64 rows in the grid
-                let identifier = Identifier {
-                    piece_kind: kind,
-                    push_idx: last_push_idx,
-                    grid: grid[highest_z - 63..=highest_z]
-                        .try_into()
-                        .expect("slice with incorrect length"),
-                };
-                if let Some((prev_height, prev_piece_idx)) =
-                    cache.insert(identifier, (highest_z, i))
-                {
-                    // We got a cache hit! Let's compare the two states to know the period.
-                    let height_diff = highest_z - prev_height;
-                    let piece_diff = i - prev_piece_idx;
-                    // Now we know how many periods (cycles) we can skip without affecting the state/repetition
-                    let skip_repeats = (1_000_000_000_000_usize - i) / piece_diff;
-                    let skip_pieces = skip_repeats * piece_diff;
-                    let skip_height = skip_repeats * height_diff;
-                    // We fast-forward our piece counter
-                    i += skip_pieces;
-                    // We record how much height we skipped, which we will add to the final result to get the real value
-                    skipped = skip_height;
+    /// The cycle skipping is compared with a plain simulation, for a number of pieces that is small enough to simulate
+    /// but large enough for the stack to repeat itself
+    fn differential<R: Rng>(rng: &mut R) -> Option<(Self::Input, Vec<String>)> {
+        let input = (0..rng.gen_range(1..=50))
+            .map(|_| {
+                if rng.gen_bool(0.5) {
+                    Push::Left
+                } else {
+                    Push::Right
                 }
-            }
-            i += 1;
+            })
+            .collect_vec();
+        let pieces = rng.gen_range(1..=5000);
+        let mut disagreements = Vec::new();
+        let (fast, reference) = (
+            stack_height_with_cycles(&input, pieces),
+            stack_height(&input, pieces),
+        );
+        if fast != reference {
+            disagreements.push(format!(
+                "part 2 with {pieces} pieces: got {fast}, reference gave {reference}"
+            ));
+        }
+        Some((input, disagreements))
+    }
+
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] =
+        &[("pieces N", "height of the stack after N pieces")];
+
+    fn repl_command(input: &Self::Input, command: &str, args: &[&str]) -> Result<(), String> {
+        if command != "pieces" {
+            return Err(format!("Unknown command {command}"));
+        }
+        let pieces = arg(args, 0, "N")?;
+        println!("{}", stack_height_with_cycles(input, pieces));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    /// Skipping the repeats must not go past the last piece, which happened when the pieces left after a cycle was found
+    /// were a multiple of its length
+    #[test]
+    fn cycle_skip_stops_at_last_piece() {
+        let (_, input) = Day17::parse(">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>").unwrap();
+        for pieces in 1..=1500 {
+            assert_eq!(
+                stack_height_with_cycles(&input, pieces),
+                stack_height(&input, pieces),
+                "{pieces} pieces"
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            prop_oneof![Just(Push::Left), Just(Push::Right)],
+            1..100,
+        )) {
+            assert_roundtrip::<Day17>(&input);
         }
-        highest_z + skipped
     }
 }
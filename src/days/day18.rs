@@ -1,5 +1,6 @@
 use std::collections::{HashSet, VecDeque};
 
+use itertools::Itertools;
 use nom::{
     character::complete::{char, line_ending, u8},
     combinator::map,
@@ -7,34 +8,26 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::{seq::SliceRandom, Rng};
 
-use crate::days::Day;
-
-const DIRS: [(i8, i8, i8); 6] = [
-    (1, 0, 0),
-    (-1, 0, 0),
-    (0, 1, 0),
-    (0, -1, 0),
-    (0, 0, 1),
-    (0, 0, -1),
-];
+use crate::{
+    days::Day,
+    geom::{Point3, Vector3},
+};
 
 const GRID_SIZE: usize = 22;
 
-#[derive(Debug)]
-pub struct Voxel {
-    pub x: usize,
-    pub y: usize,
-    pub z: usize,
+/// Coordinates of a voxel, which all fit in a `GRID_SIZE` cube
+pub type Voxel = Point3<i8>;
+
+fn in_bounds(c: Voxel) -> bool {
+    let range = 0..GRID_SIZE as i8;
+    range.contains(&c.x) && range.contains(&c.y) && range.contains(&c.z)
 }
 
-fn in_bounds(c: (i8, i8, i8)) -> bool {
-    c.0 >= 0
-        && c.0 < GRID_SIZE as i8
-        && c.1 >= 0
-        && c.1 < GRID_SIZE as i8
-        && c.2 >= 0
-        && c.2 < GRID_SIZE as i8
+/// Check whether the voxel at a given position is lava
+fn is_lava(vol: &[[[bool; GRID_SIZE]; GRID_SIZE]; GRID_SIZE], c: Voxel) -> bool {
+    vol[c.x as usize][c.y as usize][c.z as usize]
 }
 
 pub struct Day18;
@@ -48,14 +41,39 @@ impl Day for Day18 {
             map(
                 tuple((u8, char(','), u8, char(','), u8)),
                 |(x, _, y, _, z)| Voxel {
-                    x: (x + 1) as usize, // offset by one to make sure we have margin around the object
-                    y: (y + 1) as usize,
-                    z: (z + 1) as usize,
+                    x: x as i8 + 1, // offset by one to make sure we have margin around the object
+                    y: y as i8 + 1,
+                    z: z as i8 + 1,
                 },
             ),
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|v| format!("{},{},{}", v.x - 1, v.y - 1, v.z - 1))
+            .join("\n")
+    }
+
+    /// The droplet is a ball with a few holes and air pockets, which must fit in our fixed-size grid, so `scale` is
+    /// ignored
+    fn generate<R: Rng>(rng: &mut R, _scale: usize) -> Self::Input {
+        let center = Voxel::new(10, 10, 10);
+        let mut voxels = (0..20)
+            .cartesian_product(0..20)
+            .cartesian_product(0..20)
+            .map(|((x, y), z)| Voxel::new(x + 1, y + 1, z + 1))
+            .filter(|v| {
+                let (dx, dy, dz) = (v.x - center.x, v.y - center.y, v.z - center.z);
+                (dx as i32).pow(2) + (dy as i32).pow(2) + (dz as i32).pow(2) <= 81
+                    && rng.gen_bool(0.9)
+            })
+            .collect_vec();
+        voxels.shuffle(rng);
+        voxels
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 0.0259ms
@@ -65,14 +83,13 @@ impl Day for Day18 {
         let mut open_sides = 0;
         // Populate the array from the input data
         for Voxel { x, y, z } in input.iter() {
-            vol[*x][*y][*z] = true;
+            vol[*x as usize][*y as usize][*z as usize] = true;
         }
         // Let's check the open faces for each voxel (top, bottom, left, right, front, back)
-        for Voxel { x, y, z } in input.iter() {
-            for dir in DIRS {
+        for voxel in input.iter() {
+            for dir in Vector3::FACES {
                 // this will never be out of bounds because we added 1 voxel of margin in all directions
-                let n = (*x as i8 + dir.0, *y as i8 + dir.1, *z as i8 + dir.2);
-                if !vol[n.0 as usize][n.1 as usize][n.2 as usize] {
+                if !is_lava(&vol, *voxel + dir) {
                     open_sides += 1;
                 }
             }
@@ -87,22 +104,22 @@ impl Day for Day18 {
         let mut vol = [[[false; GRID_SIZE]; GRID_SIZE]; GRID_SIZE];
         // Populate the array from the input data
         for Voxel { x, y, z } in input.iter() {
-            vol[*x][*y][*z] = true;
+            vol[*x as usize][*y as usize][*z as usize] = true;
         }
         // keeping track of sides visible from the outside
         let mut visible = 0;
         // memoization to keep track of flooded voxels
-        let mut seen = HashSet::<(i8, i8, i8)>::new();
+        let mut seen = HashSet::<Voxel>::new();
         // we seed the flooding at 0, 0, 0
-        seen.insert((0, 0, 0));
+        seen.insert(Voxel::default());
         // stack for BFS
-        let mut stack = VecDeque::<(i8, i8, i8)>::new();
-        stack.push_back((0, 0, 0));
+        let mut stack = VecDeque::<Voxel>::new();
+        stack.push_back(Voxel::default());
         // let's flood it
         while let Some(voxel) = stack.pop_front() {
-            for dir in DIRS {
+            for dir in Vector3::FACES {
                 // check all neighbours
-                let n = (voxel.0 + dir.0, voxel.1 + dir.1, voxel.2 + dir.2);
+                let n = voxel + dir;
 
                 // we only consider the ones that are within bounds and not visited before
                 if seen.contains(&n) || !in_bounds(n) {
@@ -113,7 +130,7 @@ impl Day for Day18 {
                 // we will only count it once since we added the currently visited voxel to the "seen" list
                 // other faces of that lava voxel will be counted at another time when it's reached from other
                 // directions
-                if vol[n.0 as usize][n.1 as usize][n.2 as usize] {
+                if is_lava(&vol, n) {
                     visible += 1;
                     continue;
                 }
@@ -125,3 +142,22 @@ impl Day for Day18 {
         visible
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            (1..GRID_SIZE as i8 - 1, 1..GRID_SIZE as i8 - 1, 1..GRID_SIZE as i8 - 1)
+                .prop_map(|(x, y, z)| Voxel { x, y, z }),
+            0..50,
+        )) {
+            assert_roundtrip::<Day18>(&input);
+        }
+    }
+}
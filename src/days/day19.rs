@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use itertools::{Itertools, MinMaxResult};
 use nom::{
     bytes::complete::tag,
     character::complete::{line_ending, u64},
@@ -8,11 +9,12 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use rand::Rng;
 use rayon::prelude::*;
 
-use crate::days::Day;
+use crate::{days::Day, repl::arg};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Blueprint {
     pub id: u64,
     pub ore_cost_ore: u64,
@@ -36,10 +38,35 @@ struct StackItem {
     time_remaining: u64,
 }
 
-// if we build one robot per minute until the end, and each produces 1 unit per minute,
-// how much resources will they produce?
-fn theoretical_max(time_remaining: u64) -> u64 {
-    (time_remaining * (time_remaining + 1)) / 2
+/// The geodes we would open if ore and clay were free, building an obsidian robot each minute along with a geode
+/// robot whenever we have enough obsidian
+///
+/// Nothing can do better from this state, so it's no use exploring it if this doesn't beat the record.
+fn most_geodes(c: &StackItem, bp: &Blueprint) -> u64 {
+    let (mut obs, mut obs_robots, mut geodes, mut geode_robots) =
+        (c.obs, c.obs_robots, c.geodes, c.geode_robots);
+    for _ in 0..c.time_remaining {
+        geodes += geode_robots;
+        if obs >= bp.geode_cost_obs {
+            obs = obs - bp.geode_cost_obs + obs_robots;
+            geode_robots += 1;
+        } else {
+            obs += obs_robots;
+        }
+        obs_robots += 1;
+    }
+    geodes
+}
+
+/// Drop the resources which can't be spent anymore, since we can build at most one robot per minute
+///
+/// States which only differ by such a surplus give the same number of geodes, so they don't need to be told apart.
+fn without_surplus(mut item: StackItem, bp: &Blueprint, max_ore_cost: u64) -> StackItem {
+    let t = item.time_remaining;
+    item.ore = item.ore.min(max_ore_cost * t);
+    item.clay = item.clay.min(bp.obs_cost_clay * t);
+    item.obs = item.obs.min(bp.geode_cost_obs * t);
+    item
 }
 
 // Depth-first search on the solution space, pruning some branches that don't make sense
@@ -73,107 +100,180 @@ fn geodes_opened(bp: &Blueprint, time: u64) -> u64 {
             continue;
         }
 
-        // in the theoretical case where we could produce 1 geode robot per minute until the end,
-        // we should at least aim to be able to beat the record
-        if c.geodes + theoretical_max(c.time_remaining) + c.geode_robots * c.time_remaining
-            < geodes_opened
-        {
-            // We cannot achieve a better result from this state even if we could build a robot in every step
+        // even with free ore and clay, we should at least aim to be able to beat the record
+        if most_geodes(&c, bp) <= geodes_opened {
+            // We cannot achieve a better result from this state
             continue;
         }
 
-        // check if we can build a geode robot (we always need more)
-        if c.ore >= bp.geode_cost_ore && c.obs >= bp.geode_cost_obs {
+        // the states are explored from the last one pushed, so building a geode robot comes first: finding a good
+        // number of geodes early lets the check above prune more states
+
+        // we can always wait to gather more resources
+        let next = StackItem {
+            ore_robots: c.ore_robots,
+            clay_robots: c.clay_robots,
+            obs_robots: c.obs_robots,
+            geode_robots: c.geode_robots,
+            ore: c.ore + c.ore_robots,
+            clay: c.clay + c.clay_robots,
+            obs: c.obs + c.obs_robots,
+            geodes: c.geodes + c.geode_robots,
+            time_remaining: c.time_remaining - 1,
+        };
+        let next = without_surplus(next, bp, max_ore_cost);
+        let new = cache.insert(next.clone());
+        if new {
+            stack.push(next);
+        }
+
+        // check if we can build an ore robot and if we need it
+        // if we have enough robots to produce max_ore_cost in each round, no need for more robots
+        if c.ore >= bp.ore_cost_ore && c.ore_robots < max_ore_cost {
             let next = StackItem {
-                ore_robots: c.ore_robots,
+                ore_robots: c.ore_robots + 1,
                 clay_robots: c.clay_robots,
                 obs_robots: c.obs_robots,
-                geode_robots: c.geode_robots + 1,
-                ore: c.ore - bp.geode_cost_ore + c.ore_robots,
+                geode_robots: c.geode_robots,
+                ore: c.ore - bp.ore_cost_ore + c.ore_robots,
                 clay: c.clay + c.clay_robots,
-                obs: c.obs - bp.geode_cost_obs + c.obs_robots,
+                obs: c.obs + c.obs_robots,
                 geodes: c.geodes + c.geode_robots,
                 time_remaining: c.time_remaining - 1,
             };
+            let next = without_surplus(next, bp, max_ore_cost);
             let new = cache.insert(next.clone());
             if new {
                 stack.push(next);
             }
         }
 
-        // check if we can build an obsidian robot
-        if c.ore >= bp.obs_cost_ore && c.clay >= bp.obs_cost_clay {
+        // check if we can build a clay robot and if we need it
+        // we can only spend obs_cost_clay clay per round, more robots would be useless
+        if c.ore >= bp.clay_cost_ore && c.clay_robots < bp.obs_cost_clay {
             let next = StackItem {
                 ore_robots: c.ore_robots,
-                clay_robots: c.clay_robots,
-                obs_robots: c.obs_robots + 1,
+                clay_robots: c.clay_robots + 1,
+                obs_robots: c.obs_robots,
                 geode_robots: c.geode_robots,
-                ore: c.ore - bp.obs_cost_ore + c.ore_robots,
-                clay: c.clay - bp.obs_cost_clay + c.clay_robots,
+                ore: c.ore - bp.clay_cost_ore + c.ore_robots,
+                clay: c.clay + c.clay_robots,
                 obs: c.obs + c.obs_robots,
                 geodes: c.geodes + c.geode_robots,
                 time_remaining: c.time_remaining - 1,
             };
+            let next = without_surplus(next, bp, max_ore_cost);
             let new = cache.insert(next.clone());
             if new {
                 stack.push(next);
             }
         }
 
-        // check if we can build a clay robot
-        if c.ore >= bp.clay_cost_ore {
+        // check if we can build an obsidian robot and if we need it
+        // we can only spend geode_cost_obs obsidian per round, more robots would be useless
+        if c.ore >= bp.obs_cost_ore
+            && c.clay >= bp.obs_cost_clay
+            && c.obs_robots < bp.geode_cost_obs
+        {
             let next = StackItem {
                 ore_robots: c.ore_robots,
-                clay_robots: c.clay_robots + 1,
-                obs_robots: c.obs_robots,
+                clay_robots: c.clay_robots,
+                obs_robots: c.obs_robots + 1,
                 geode_robots: c.geode_robots,
-                ore: c.ore - bp.clay_cost_ore + c.ore_robots,
-                clay: c.clay + c.clay_robots,
+                ore: c.ore - bp.obs_cost_ore + c.ore_robots,
+                clay: c.clay - bp.obs_cost_clay + c.clay_robots,
                 obs: c.obs + c.obs_robots,
                 geodes: c.geodes + c.geode_robots,
                 time_remaining: c.time_remaining - 1,
             };
+            let next = without_surplus(next, bp, max_ore_cost);
             let new = cache.insert(next.clone());
             if new {
                 stack.push(next);
             }
         }
 
-        // check if we can build an ore robot and if we need it
-        // if we have enough robots to produce max_ore_cost in each round, no need for more robots
-        if c.ore >= bp.ore_cost_ore && c.ore_robots < max_ore_cost {
+        // check if we can build a geode robot (we always need more)
+        if c.ore >= bp.geode_cost_ore && c.obs >= bp.geode_cost_obs {
             let next = StackItem {
-                ore_robots: c.ore_robots + 1,
+                ore_robots: c.ore_robots,
                 clay_robots: c.clay_robots,
                 obs_robots: c.obs_robots,
-                geode_robots: c.geode_robots,
-                ore: c.ore - bp.ore_cost_ore + c.ore_robots,
+                geode_robots: c.geode_robots + 1,
+                ore: c.ore - bp.geode_cost_ore + c.ore_robots,
                 clay: c.clay + c.clay_robots,
-                obs: c.obs + c.obs_robots,
+                obs: c.obs - bp.geode_cost_obs + c.obs_robots,
                 geodes: c.geodes + c.geode_robots,
                 time_remaining: c.time_remaining - 1,
             };
+            let next = without_surplus(next, bp, max_ore_cost);
             let new = cache.insert(next.clone());
             if new {
                 stack.push(next);
             }
         }
+    }
+    geodes_opened
+}
 
-        // we can always wait to gather more resources
-        let next = StackItem {
-            ore_robots: c.ore_robots,
-            clay_robots: c.clay_robots,
-            obs_robots: c.obs_robots,
-            geode_robots: c.geode_robots,
+/// Depth-first search on the whole solution space, only skipping the states that were already visited
+fn geodes_opened_reference(bp: &Blueprint, time: u64) -> u64 {
+    let mut cache = HashSet::<StackItem>::new();
+    let mut stack = vec![StackItem {
+        ore_robots: 1,
+        time_remaining: time,
+        ..Default::default()
+    }];
+    let mut geodes_opened = 0;
+    while let Some(c) = stack.pop() {
+        if c.time_remaining == 0 {
+            geodes_opened = geodes_opened.max(c.geodes);
+            continue;
+        }
+        // waiting, then building each kind of robot if we can afford it
+        let wait = StackItem {
             ore: c.ore + c.ore_robots,
             clay: c.clay + c.clay_robots,
             obs: c.obs + c.obs_robots,
             geodes: c.geodes + c.geode_robots,
             time_remaining: c.time_remaining - 1,
+            ..c.clone()
         };
-        let new = cache.insert(next.clone());
-        if new {
-            stack.push(next);
+        let mut next = vec![wait.clone()];
+        if c.ore >= bp.ore_cost_ore {
+            next.push(StackItem {
+                ore_robots: c.ore_robots + 1,
+                ore: wait.ore - bp.ore_cost_ore,
+                ..wait.clone()
+            });
+        }
+        if c.ore >= bp.clay_cost_ore {
+            next.push(StackItem {
+                clay_robots: c.clay_robots + 1,
+                ore: wait.ore - bp.clay_cost_ore,
+                ..wait.clone()
+            });
+        }
+        if c.ore >= bp.obs_cost_ore && c.clay >= bp.obs_cost_clay {
+            next.push(StackItem {
+                obs_robots: c.obs_robots + 1,
+                ore: wait.ore - bp.obs_cost_ore,
+                clay: wait.clay - bp.obs_cost_clay,
+                ..wait.clone()
+            });
+        }
+        if c.ore >= bp.geode_cost_ore && c.obs >= bp.geode_cost_obs {
+            next.push(StackItem {
+                geode_robots: c.geode_robots + 1,
+                ore: wait.ore - bp.geode_cost_ore,
+                obs: wait.obs - bp.geode_cost_obs,
+                ..wait.clone()
+            });
+        }
+        for item in next {
+            if cache.insert(item.clone()) {
+                stack.push(item);
+            }
         }
     }
     geodes_opened
@@ -192,6 +292,55 @@ impl Day for Day19 {
         separated_list0(line_ending, parse_blueprint)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .iter()
+            .map(|bp| {
+                format!(
+                    "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.",
+                    bp.id,
+                    bp.ore_cost_ore,
+                    bp.clay_cost_ore,
+                    bp.obs_cost_ore,
+                    bp.obs_cost_clay,
+                    bp.geode_cost_ore,
+                    bp.geode_cost_obs
+                )
+            })
+            .join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (1..=30 * scale as u64)
+            .map(|id| Blueprint {
+                id,
+                ore_cost_ore: rng.gen_range(2..=4),
+                clay_cost_ore: rng.gen_range(2..=4),
+                obs_cost_ore: rng.gen_range(2..=4),
+                obs_cost_clay: rng.gen_range(7..=20),
+                geode_cost_ore: rng.gen_range(2..=4),
+                geode_cost_obs: rng.gen_range(7..=20),
+            })
+            .collect()
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let range = |cost: fn(&Blueprint) -> u64| match input.iter().map(cost).minmax() {
+            MinMaxResult::NoElements => "none".to_string(),
+            MinMaxResult::OneElement(cost) => cost.to_string(),
+            MinMaxResult::MinMax(min, max) => format!("{min}..={max}"),
+        };
+        vec![
+            ("blueprints", input.len().to_string()),
+            ("ore robot ore", range(|b| b.ore_cost_ore)),
+            ("clay robot ore", range(|b| b.clay_cost_ore)),
+            ("obsidian robot ore", range(|b| b.obs_cost_ore)),
+            ("obsidian robot clay", range(|b| b.obs_cost_clay)),
+            ("geode robot ore", range(|b| b.geode_cost_ore)),
+            ("geode robot obsidian", range(|b| b.geode_cost_obs)),
+        ]
+    }
+
     type Output1 = u64;
 
     fn part_1(input: &Self::Input) -> Self::Output1 {
@@ -207,6 +356,50 @@ impl Day for Day19 {
             .map(|bp| geodes_opened(bp, 32))
             .product()
     }
+
+    /// The pruning is compared with exploring every state, for a time that is short enough to explore them all
+    fn differential<R: Rng>(rng: &mut R) -> Option<(Self::Input, Vec<String>)> {
+        // cheap obsidian and geode robots, so that geodes get opened in the short time
+        let input = (1..=rng.gen_range(1..=3))
+            .map(|id| Blueprint {
+                id,
+                ore_cost_ore: rng.gen_range(1..=4),
+                clay_cost_ore: rng.gen_range(1..=4),
+                obs_cost_ore: rng.gen_range(1..=4),
+                obs_cost_clay: rng.gen_range(1..=8),
+                geode_cost_ore: rng.gen_range(1..=4),
+                geode_cost_obs: rng.gen_range(1..=8),
+            })
+            .collect_vec();
+        let time = rng.gen_range(1..=13);
+        let mut disagreements = Vec::new();
+        for bp in &input {
+            let (fast, reference) = (geodes_opened(bp, time), geodes_opened_reference(bp, time));
+            if fast != reference {
+                disagreements.push(format!(
+                    "blueprint {} in {time} minutes: got {fast}, reference gave {reference}",
+                    bp.id
+                ));
+            }
+        }
+        Some((input, disagreements))
+    }
+
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] = &[(
+        "minutes N",
+        "geodes opened by each blueprint in the given time",
+    )];
+
+    fn repl_command(input: &Self::Input, command: &str, args: &[&str]) -> Result<(), String> {
+        if command != "minutes" {
+            return Err(format!("Unknown command {command}"));
+        }
+        let time = arg(args, 0, "N")?;
+        for bp in input {
+            println!("Blueprint {}: {}", bp.id, geodes_opened(bp, time));
+        }
+        Ok(())
+    }
 }
 
 fn parse_blueprint(input: &str) -> IResult<&str, Blueprint> {
@@ -255,3 +448,46 @@ fn parse_blueprint(input: &str) -> IResult<&str, Blueprint> {
         },
     )(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    /// The example blueprints of the puzzle
+    #[test]
+    fn example() {
+        let (_, input) = Day19::parse("Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.\nBlueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.").unwrap();
+        assert_eq!(Day19::part_1(&input), 33);
+        assert_eq!(Day19::part_2(&input), 56 * 62);
+    }
+
+    /// Generated inputs of the size of a real one can be solved, which takes a lot of memory without enough pruning
+    #[test]
+    fn generated() {
+        let input = Day19::generate(&mut StdRng::seed_from_u64(1), 1);
+        assert!(Day19::part_1(&input) > 0);
+        assert!(Day19::part_2(&input) > 0);
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(
+            any::<[u64; 7]>().prop_map(|c| Blueprint {
+                id: c[0],
+                ore_cost_ore: c[1],
+                clay_cost_ore: c[2],
+                obs_cost_ore: c[3],
+                obs_cost_clay: c[4],
+                geode_cost_ore: c[5],
+                geode_cost_obs: c[6],
+            }),
+            0..10,
+        )) {
+            assert_roundtrip::<Day19>(&input);
+        }
+    }
+}
@@ -1,10 +1,12 @@
+use itertools::Itertools;
 use nom::{
     character::complete::{i64, line_ending},
     multi::separated_list0,
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::days::{Day, Variant};
 
 const LENGTH: usize = 5000;
 
@@ -25,6 +27,19 @@ fn mix(val: &[i64], idx: &mut Vec<usize>) {
     }
 }
 
+/// Mix the values `rounds` times with the given mixing function after multiplying them by the decryption key, and
+/// compute the grove's coordinates
+fn decrypt(input: &[i64], key: i64, rounds: usize, mix: impl Fn(&[i64], &mut Vec<usize>)) -> i64 {
+    let val = input.iter().map(|v| v * key).collect::<Vec<_>>();
+    let mut idx = (0..val.len()).collect::<Vec<_>>();
+    for _ in 0..rounds {
+        mix(&val, &mut idx);
+    }
+    // reconstruct the list with actual values in correct order
+    let val = idx.iter().map(|&i| val[i]).collect::<Vec<_>>();
+    calculate_output(&val)
+}
+
 /// Take the 3 items of interest in the list and sum them to get the coordinate of the grove
 ///
 /// We get the 1000th, 2000th and 3000th item after the zero in the list
@@ -47,16 +62,40 @@ impl Day for Day20 {
         separated_list0(line_ending, i64)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input.iter().join("\n")
+    }
+
+    /// The numbers can repeat, except for the zero which must be unique
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut numbers = (1..LENGTH * scale)
+            .map(|_| {
+                let n = rng.gen_range(1..=10_000);
+                if rng.gen_bool(0.5) {
+                    n
+                } else {
+                    -n
+                }
+            })
+            .collect_vec();
+        numbers.insert(rng.gen_range(0..=numbers.len()), 0);
+        numbers
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        // the numbers with the same value as an earlier one, which can't be told apart by their value
+        let duplicates = input.len() - input.iter().unique().count();
+        vec![
+            ("numbers", input.len().to_string()),
+            ("duplicates", duplicates.to_string()),
+        ]
+    }
+
     type Output1 = i64;
 
     /// Part 1 took 4.4116ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
-        let val = input.clone();
-        let mut idx = (0..val.len()).collect::<Vec<_>>();
-        mix(&val, &mut idx);
-        // reconstruct the list with actual values in correct order
-        let val = idx.iter().map(|&i| val[i]).collect::<Vec<_>>();
-        calculate_output(&val)
+        decrypt(input, 1, 1, mix)
     }
 
     type Output2 = i64;
@@ -65,14 +104,19 @@ impl Day for Day20 {
     fn part_2(input: &Self::Input) -> Self::Output2 {
         // for this part, we have to multiply the values by 811589153, which doesn't affect the code/perf since we
         // modulo the shift amount (the values of the `val` list)
-        let val = input.iter().map(|v| v * 811589153).collect::<Vec<_>>();
-        let mut idx = (0..val.len()).collect::<Vec<_>>();
-        for _ in 0..10 {
-            mix(&val, &mut idx);
-        }
-        // reconstruct the list with actual values in correct order
-        let val = idx.iter().map(|&i| val[i]).collect::<Vec<_>>();
-        calculate_output(&val)
+        decrypt(input, 811589153, 10, mix)
+    }
+
+    fn variants_1() -> Vec<Variant<Self::Input, Self::Output1>> {
+        vec![("swaps", |input| {
+            decrypt(input, 1, 1, |val, idx| mix_old(val, idx))
+        })]
+    }
+
+    fn variants_2() -> Vec<Variant<Self::Input, Self::Output2>> {
+        vec![("swaps", |input| {
+            decrypt(input, 811589153, 10, |val, idx| mix_old(val, idx))
+        })]
     }
 }
 
@@ -83,15 +127,15 @@ impl Day for Day20 {
 /// The `idx` array keeps track of where the items were initially. We apply transformations on it.
 /// We will later reconstruct the mixed input with the help of this shuffled idx list.
 /// The fact that the first and last item get swapped is not important, since our array is cyclic.
-#[allow(unused)]
-fn mix_old(val: &[i64; LENGTH], idx: &mut [usize; LENGTH]) {
+fn mix_old(val: &[i64], idx: &mut [usize]) {
+    let len = val.len() as i64;
     // for each item in the original list
     for (i, v) in val.iter().enumerate() {
         // let's find where the item is now in the mixed list (find the position of i in the idx list)
         let x = idx.iter().position(|&idx| idx == i).unwrap();
         // to avoid going more than once around the cycle, we take the modulo by L-1!
         // (since the first and last positions are contiguous in the cycle)
-        let shift = v % (LENGTH as i64 - 1);
+        let shift = v % (len - 1);
         // dir will be 1 if we go in positive, or -1 else (and 0 if we don't move)
         let dir = shift.signum();
         // we have to move the item until we reach position `stop`
@@ -102,9 +146,9 @@ fn mix_old(val: &[i64; LENGTH], idx: &mut [usize; LENGTH]) {
         while j != stop {
             // we take the non-negative remainder (mathematical "mod") of the current position
             // (so that -1 is equivalent to L-1)
-            let j_wrap = j.rem_euclid(LENGTH as i64) as usize;
+            let j_wrap = j.rem_euclid(len) as usize;
             // we will swap it with the next in the iterating direction (also wrapping around with the "mod")
-            let j_next = (j + dir).rem_euclid(LENGTH as i64) as usize;
+            let j_next = (j + dir).rem_euclid(len) as usize;
             // swap the values, effectively moving our target by one
             idx.swap(j_wrap, j_next);
 
@@ -112,3 +156,18 @@ fn mix_old(val: &[i64; LENGTH], idx: &mut [usize; LENGTH]) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(any::<i64>(), 0..100)) {
+            assert_roundtrip::<Day20>(&input);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -9,10 +10,11 @@ use nom::{
     sequence::{separated_pair, tuple},
     IResult,
 };
+use rand::{seq::IteratorRandom, Rng};
 
 use crate::days::Day;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Operator {
     Add,
     Sub,
@@ -32,7 +34,18 @@ impl From<char> for Operator {
     }
 }
 
-#[derive(Debug)]
+impl From<&Operator> for char {
+    fn from(op: &Operator) -> Self {
+        match op {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mult => '*',
+            Operator::Div => '/',
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
 pub struct Operation {
     pub left: String,
     pub right: String,
@@ -75,13 +88,13 @@ impl Operation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum MonkeyType {
     Number(i64),
     Operation(Operation),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Monkey {
     pub name: String,
     pub mtype: MonkeyType,
@@ -191,6 +204,91 @@ fn find_humn_value(
     }
 }
 
+/// Add a monkey with a random name which is not used yet, and return its name
+fn add_monkey<R: Rng>(
+    rng: &mut R,
+    monkeys: &mut HashMap<String, Monkey>,
+    mtype: MonkeyType,
+) -> String {
+    let name = loop {
+        let name: String = (0..4).map(|_| rng.gen_range('a'..='z')).collect();
+        if name != "root" && !monkeys.contains_key(&name) {
+            break name;
+        }
+    };
+    monkeys.insert(
+        name.clone(),
+        Monkey {
+            name: name.clone(),
+            mtype,
+        },
+    );
+    name
+}
+
+/// Add a random tree of `size` monkeys, and return the name and value of the monkey at its top
+///
+/// All the values are positive and at most 1 billion, and all the divisions are exact.
+fn generate_tree<R: Rng>(
+    rng: &mut R,
+    size: usize,
+    monkeys: &mut HashMap<String, Monkey>,
+) -> (String, i64) {
+    if size < 3 {
+        let value = rng.gen_range(1..=20);
+        return (add_monkey(rng, monkeys, MonkeyType::Number(value)), value);
+    }
+    let left_size = rng.gen_range(0..(size - 1) / 2) * 2 + 1;
+    let mut left = generate_tree(rng, left_size, monkeys);
+    let mut right = generate_tree(rng, size - 1 - left_size, monkeys);
+    if left.1 < right.1 {
+        // with the largest value on the left, at least one of the operators can be used
+        std::mem::swap(&mut left, &mut right);
+    }
+    let mut operators = [Operator::Add, Operator::Sub, Operator::Mult, Operator::Div]
+        .into_iter()
+        .filter(|op| match op {
+            Operator::Add => left.1 + right.1 <= 1_000_000_000,
+            Operator::Sub => left.1 > right.1,
+            Operator::Mult => left.1 * right.1 <= 1_000_000_000,
+            Operator::Div => left.1 % right.1 == 0,
+        })
+        .collect_vec();
+    let operation = Operation {
+        left: left.0,
+        right: right.0,
+        operator: operators.swap_remove(rng.gen_range(0..operators.len())),
+    };
+    let value = operation.calc(left.1, right.1);
+    (
+        add_monkey(rng, monkeys, MonkeyType::Operation(operation)),
+        value,
+    )
+}
+
+/// Number of operations between the monkey and the deepest number below it
+fn depth(name: &str, monkeys: &HashMap<String, Monkey>) -> usize {
+    match monkeys.get(name).map(|m| &m.mtype) {
+        Some(MonkeyType::Operation(op)) => {
+            1 + depth(&op.left, monkeys).max(depth(&op.right, monkeys))
+        }
+        _ => 0,
+    }
+}
+
+/// Number of operations between the monkey and `humn`, if it depends on it
+fn path_len(name: &str, monkeys: &HashMap<String, Monkey>) -> Option<usize> {
+    if name == "humn" {
+        return Some(0);
+    }
+    match monkeys.get(name).map(|m| &m.mtype) {
+        Some(MonkeyType::Operation(op)) => path_len(&op.left, monkeys)
+            .or_else(|| path_len(&op.right, monkeys))
+            .map(|len| len + 1),
+        _ => None,
+    }
+}
+
 pub struct Day21;
 
 impl Day for Day21 {
@@ -205,6 +303,132 @@ impl Day for Day21 {
         Ok((rest, map))
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input
+            .values()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .map(|monkey| match &monkey.mtype {
+                MonkeyType::Number(n) => format!("{}: {n}", monkey.name),
+                MonkeyType::Operation(op) => format!(
+                    "{}: {} {} {}",
+                    monkey.name,
+                    op.left,
+                    char::from(&op.operator),
+                    op.right
+                ),
+            })
+            .join("\n")
+    }
+
+    /// "humn" is at the bottom of a long chain of operations, each combining the chain with a random tree of monkeys,
+    /// and the divisions are exact both with the number "humn" yells and with the number we should yell
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let mut monkeys = HashMap::new();
+        // the values along the chain, with the number that "humn" yells (part 1) and the one we should yell (part 2)
+        let mut values = [rng.gen_range(1..=5000), rng.gen_range(1..=1_000_000)];
+        monkeys.insert(
+            "humn".to_string(),
+            Monkey {
+                name: "humn".to_string(),
+                mtype: MonkeyType::Number(values[0]),
+            },
+        );
+        let mut chain = "humn".to_string();
+        for _ in 0..70 * scale {
+            // each step has an operator, the other operand with its value, and whether the chain is on the left
+            let mut steps = vec![];
+            if values.iter().all(|v| v.abs() < 1_000_000_000_000) && rng.gen_bool(0.3) {
+                let factor = rng.gen_range(2..=9);
+                let name = add_monkey(rng, &mut monkeys, MonkeyType::Number(factor));
+                steps.push((Operator::Mult, name, factor, rng.gen_bool(0.5)));
+                if rng.gen_bool(0.5) {
+                    // dividing right after a multiplication keeps the division exact, whatever the chain's value
+                    let divisor = (2..=factor)
+                        .filter(|d| factor % d == 0)
+                        .choose(rng)
+                        .unwrap();
+                    let name = add_monkey(rng, &mut monkeys, MonkeyType::Number(divisor));
+                    steps.push((Operator::Div, name, divisor, true));
+                }
+            } else {
+                let size = rng.gen_range(0..32) * 2 + 1;
+                let (name, value) = generate_tree(rng, size, &mut monkeys);
+                let operator = if rng.gen_bool(0.5) {
+                    Operator::Add
+                } else {
+                    Operator::Sub
+                };
+                steps.push((operator, name, value, rng.gen_bool(0.5)));
+            }
+            for (operator, other, other_value, chain_left) in steps {
+                let (left, right) = if chain_left {
+                    (chain, other)
+                } else {
+                    (other, chain)
+                };
+                let operation = Operation {
+                    left,
+                    right,
+                    operator,
+                };
+                values = values.map(|v| {
+                    if chain_left {
+                        operation.calc(v, other_value)
+                    } else {
+                        operation.calc(other_value, v)
+                    }
+                });
+                chain = add_monkey(rng, &mut monkeys, MonkeyType::Operation(operation));
+            }
+        }
+        // the other side of the root is equal to the chain when we yell the right number
+        let size = 1000 * scale + 1;
+        let (tree, tree_value) = generate_tree(rng, size, &mut monkeys);
+        let (operator, difference) = if values[1] >= tree_value {
+            (Operator::Add, values[1] - tree_value)
+        } else {
+            (Operator::Sub, tree_value - values[1])
+        };
+        let difference = add_monkey(rng, &mut monkeys, MonkeyType::Number(difference));
+        let other = add_monkey(
+            rng,
+            &mut monkeys,
+            MonkeyType::Operation(Operation {
+                left: tree,
+                right: difference,
+                operator,
+            }),
+        );
+        let (left, right) = if rng.gen_bool(0.5) {
+            (chain, other)
+        } else {
+            (other, chain)
+        };
+        monkeys.insert(
+            "root".to_string(),
+            Monkey {
+                name: "root".to_string(),
+                mtype: MonkeyType::Operation(Operation {
+                    left,
+                    right,
+                    operator: Operator::Add,
+                }),
+            },
+        );
+        monkeys
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        vec![
+            ("monkeys", input.len().to_string()),
+            ("tree depth", depth("root", input).to_string()),
+            (
+                "humn depth",
+                path_len("root", input).map_or("none".to_string(), |d| d.to_string()),
+            ),
+        ]
+    }
+
     type Output1 = i64;
 
     /// Part 1 took 0.1176ms
@@ -240,3 +464,35 @@ impl Day for Day21 {
         panic!("not found");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn mtype() -> impl Strategy<Value = MonkeyType> {
+        prop_oneof![
+            any::<i64>().prop_map(MonkeyType::Number),
+            ("[a-z]{4}", "[+*/-]", "[a-z]{4}").prop_map(|(left, op, right)| {
+                MonkeyType::Operation(Operation {
+                    left,
+                    right,
+                    operator: Operator::from(op.chars().next().unwrap()),
+                })
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(monkeys in prop::collection::hash_map("[a-z]{4}", mtype(), 0..20)) {
+            let input = monkeys
+                .into_iter()
+                .map(|(name, mtype)| (name.clone(), Monkey { name, mtype }))
+                .collect();
+            assert_roundtrip::<Day21>(&input);
+        }
+    }
+}
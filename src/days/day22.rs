@@ -9,52 +9,33 @@ use nom::{
     sequence::separated_pair,
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::{days::Day, geom::Direction};
 
 const CUBE_SIZE: usize = 50;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Tile {
     Out,
     Free,
     Wall,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Instruction {
     Walk(usize),
     RotateLeft,
     RotateRight,
 }
 
-#[derive(Debug)]
-pub enum Dir {
-    Right,
-    Down,
-    Left,
-    Up,
-}
-
-impl From<&Dir> for isize {
-    fn from(value: &Dir) -> Self {
-        match value {
-            Dir::Right => 0,
-            Dir::Down => 1,
-            Dir::Left => 2,
-            Dir::Up => 3,
-        }
-    }
-}
-
-impl From<isize> for Dir {
-    fn from(value: isize) -> Self {
-        match value {
-            0 => Dir::Right,
-            1 => Dir::Down,
-            2 => Dir::Left,
-            _ => Dir::Up,
-        }
+/// The facing value used in the final password
+fn facing(dir: Direction) -> usize {
+    match dir {
+        Direction::Right => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Up => 3,
     }
 }
 
@@ -116,141 +97,141 @@ fn face_to_global(x: usize, y: usize, face: usize) -> (usize, usize) {
     }
 }
 
-fn next_coord(x: usize, y: usize, dir: &Dir) -> (usize, usize, Dir) {
+fn next_coord(x: usize, y: usize, dir: &Direction) -> (usize, usize, Direction) {
     let face = get_face(x, y);
     let (xl, yl) = coord_in_face(x, y);
     match face {
         1 => match dir {
-            Dir::Right => (x + 1, y, Dir::Right),
-            Dir::Left => {
+            Direction::Right => (x + 1, y, Direction::Right),
+            Direction::Left => {
                 if xl == 0 {
                     let (nx, ny) = face_to_global(xl, CUBE_SIZE - yl - 1, 4);
-                    (nx, ny, Dir::Right)
+                    (nx, ny, Direction::Right)
                 } else {
-                    (x - 1, y, Dir::Left)
+                    (x - 1, y, Direction::Left)
                 }
             }
-            Dir::Up => {
+            Direction::Up => {
                 if yl == 0 {
                     let (nx, ny) = face_to_global(yl, xl, 6);
-                    (nx, ny, Dir::Right)
+                    (nx, ny, Direction::Right)
                 } else {
-                    (x, y - 1, Dir::Up)
+                    (x, y - 1, Direction::Up)
                 }
             }
-            Dir::Down => (x, y + 1, Dir::Down),
+            Direction::Down => (x, y + 1, Direction::Down),
         },
         2 => match dir {
-            Dir::Right => {
+            Direction::Right => {
                 if xl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(xl, CUBE_SIZE - yl - 1, 5);
-                    (nx, ny, Dir::Left)
+                    (nx, ny, Direction::Left)
                 } else {
-                    (x + 1, y, Dir::Right)
+                    (x + 1, y, Direction::Right)
                 }
             }
-            Dir::Left => (x - 1, y, Dir::Left),
-            Dir::Up => {
+            Direction::Left => (x - 1, y, Direction::Left),
+            Direction::Up => {
                 if yl == 0 {
                     let (nx, ny) = face_to_global(xl, CUBE_SIZE - 1, 6);
-                    (nx, ny, Dir::Up)
+                    (nx, ny, Direction::Up)
                 } else {
-                    (x, y - 1, Dir::Up)
+                    (x, y - 1, Direction::Up)
                 }
             }
-            Dir::Down => {
+            Direction::Down => {
                 if yl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(yl, xl, 3);
-                    (nx, ny, Dir::Left)
+                    (nx, ny, Direction::Left)
                 } else {
-                    (x, y + 1, Dir::Down)
+                    (x, y + 1, Direction::Down)
                 }
             }
         },
         3 => match dir {
-            Dir::Right => {
+            Direction::Right => {
                 if xl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(yl, xl, 2);
-                    (nx, ny, Dir::Up)
+                    (nx, ny, Direction::Up)
                 } else {
-                    (x + 1, y, Dir::Right)
+                    (x + 1, y, Direction::Right)
                 }
             }
-            Dir::Left => {
+            Direction::Left => {
                 if xl == 0 {
                     let (nx, ny) = face_to_global(yl, xl, 4);
-                    (nx, ny, Dir::Down)
+                    (nx, ny, Direction::Down)
                 } else {
-                    (x - 1, y, Dir::Left)
+                    (x - 1, y, Direction::Left)
                 }
             }
-            Dir::Up => (x, y - 1, Dir::Up),
-            Dir::Down => (x, y + 1, Dir::Down),
+            Direction::Up => (x, y - 1, Direction::Up),
+            Direction::Down => (x, y + 1, Direction::Down),
         },
         4 => match dir {
-            Dir::Right => (x + 1, y, Dir::Right),
-            Dir::Left => {
+            Direction::Right => (x + 1, y, Direction::Right),
+            Direction::Left => {
                 if xl == 0 {
                     let (nx, ny) = face_to_global(xl, CUBE_SIZE - yl - 1, 1);
-                    (nx, ny, Dir::Right)
+                    (nx, ny, Direction::Right)
                 } else {
-                    (x - 1, y, Dir::Left)
+                    (x - 1, y, Direction::Left)
                 }
             }
-            Dir::Up => {
+            Direction::Up => {
                 if yl == 0 {
                     let (nx, ny) = face_to_global(yl, xl, 3);
-                    (nx, ny, Dir::Right)
+                    (nx, ny, Direction::Right)
                 } else {
-                    (x, y - 1, Dir::Up)
+                    (x, y - 1, Direction::Up)
                 }
             }
-            Dir::Down => (x, y + 1, Dir::Down),
+            Direction::Down => (x, y + 1, Direction::Down),
         },
         5 => match dir {
-            Dir::Right => {
+            Direction::Right => {
                 if xl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(xl, CUBE_SIZE - yl - 1, 2);
-                    (nx, ny, Dir::Left)
+                    (nx, ny, Direction::Left)
                 } else {
-                    (x + 1, y, Dir::Right)
+                    (x + 1, y, Direction::Right)
                 }
             }
-            Dir::Left => (x - 1, y, Dir::Left),
-            Dir::Up => (x, y - 1, Dir::Up),
-            Dir::Down => {
+            Direction::Left => (x - 1, y, Direction::Left),
+            Direction::Up => (x, y - 1, Direction::Up),
+            Direction::Down => {
                 if yl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(yl, xl, 6);
-                    (nx, ny, Dir::Left)
+                    (nx, ny, Direction::Left)
                 } else {
-                    (x, y + 1, Dir::Down)
+                    (x, y + 1, Direction::Down)
                 }
             }
         },
         _ => match dir {
-            Dir::Right => {
+            Direction::Right => {
                 if xl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(yl, xl, 5);
-                    (nx, ny, Dir::Up)
+                    (nx, ny, Direction::Up)
                 } else {
-                    (x + 1, y, Dir::Right)
+                    (x + 1, y, Direction::Right)
                 }
             }
-            Dir::Left => {
+            Direction::Left => {
                 if xl == 0 {
                     let (nx, ny) = face_to_global(yl, xl, 1);
-                    (nx, ny, Dir::Down)
+                    (nx, ny, Direction::Down)
                 } else {
-                    (x - 1, y, Dir::Left)
+                    (x - 1, y, Direction::Left)
                 }
             }
-            Dir::Up => (x, y - 1, Dir::Up),
-            Dir::Down => {
+            Direction::Up => (x, y - 1, Direction::Up),
+            Direction::Down => {
                 if yl == CUBE_SIZE - 1 {
                     let (nx, ny) = face_to_global(xl, 0, 2);
-                    (nx, ny, Dir::Down)
+                    (nx, ny, Direction::Down)
                 } else {
-                    (x, y + 1, Dir::Down)
+                    (x, y + 1, Direction::Down)
                 }
             }
         },
@@ -258,7 +239,7 @@ fn next_coord(x: usize, y: usize, dir: &Dir) -> (usize, usize, Dir) {
 }
 
 #[allow(unused)]
-fn print_cube_face(px: usize, py: usize, dir: &Dir, grid: &[Vec<Tile>]) {
+fn print_cube_face(px: usize, py: usize, dir: &Direction, grid: &[Vec<Tile>]) {
     for (y, row) in grid
         .iter()
         .enumerate()
@@ -273,10 +254,10 @@ fn print_cube_face(px: usize, py: usize, dir: &Dir, grid: &[Vec<Tile>]) {
         {
             if x == px && y == py {
                 match dir {
-                    Dir::Right => print!("{}", ">".on_red()),
-                    Dir::Down => print!("{}", "v".on_red()),
-                    Dir::Left => print!("{}", "<".on_red()),
-                    Dir::Up => print!("{}", "^".on_red()),
+                    Direction::Right => print!("{}", ">".on_red()),
+                    Direction::Down => print!("{}", "v".on_red()),
+                    Direction::Left => print!("{}", "<".on_red()),
+                    Direction::Up => print!("{}", "^".on_red()),
                 }
                 continue;
             }
@@ -295,17 +276,17 @@ fn print_cube_face(px: usize, py: usize, dir: &Dir, grid: &[Vec<Tile>]) {
 pub struct Player {
     x: usize,
     y: usize,
-    dir: Dir,
+    dir: Direction,
 }
 
 impl Player {
     pub fn perform(&mut self, instr: &Instruction, grid: &[Vec<Tile>], part2: bool) {
         match instr {
             Instruction::RotateLeft => {
-                self.dir = (isize::from(&self.dir) - 1).rem_euclid(4).into();
+                self.dir = self.dir.turn_left();
             }
             Instruction::RotateRight => {
-                self.dir = (isize::from(&self.dir) + 1).rem_euclid(4).into();
+                self.dir = self.dir.turn_right();
             }
             Instruction::Walk(dist) => {
                 if part2 {
@@ -351,7 +332,7 @@ impl Player {
         let mut remaining = *dist;
         while remaining > 0 {
             let (next_x, next_y, next_tile) = match &self.dir {
-                Dir::Right => {
+                Direction::Right => {
                     let row = &grid[self.y];
                     let (next_x, next_tile) = row
                         .iter()
@@ -362,7 +343,7 @@ impl Player {
                         .unwrap();
                     (next_x, self.y, next_tile)
                 }
-                Dir::Down => {
+                Direction::Down => {
                     let col = grid.iter().map_while(|row| row.get(self.x));
                     let (next_y, next_tile) = col
                         .enumerate()
@@ -372,7 +353,7 @@ impl Player {
                         .unwrap();
                     (self.x, next_y, next_tile)
                 }
-                Dir::Left => {
+                Direction::Left => {
                     let row = &grid[self.y];
                     let (next_x, next_tile) = row
                         .iter()
@@ -384,7 +365,7 @@ impl Player {
                         .unwrap();
                     (next_x, self.y, next_tile)
                 }
-                Dir::Up => {
+                Direction::Up => {
                     let col = grid.iter().map_while(|row| row.get(self.x)).collect_vec();
                     let (next_y, next_tile) = col
                         .iter()
@@ -434,6 +415,29 @@ fn parse_sequence(input: &str) -> IResult<&str, Vec<Instruction>> {
     }))(input)
 }
 
+/// The layout of the faces on the map, numbered in reading order, like `.12` for the first row of the layout that
+/// the solver assumes
+fn cube_net(grid: &[Vec<Tile>], side: usize) -> String {
+    if side == 0 {
+        return String::new();
+    }
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let mut face = 0;
+    (0..grid.len().div_ceil(side))
+        .map(|row| {
+            (0..width.div_ceil(side))
+                .map(|col| match grid[row * side].get(col * side) {
+                    Some(Tile::Free | Tile::Wall) => {
+                        face += 1;
+                        char::from_digit(face, 10).unwrap_or('#')
+                    }
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
 pub struct Day22;
 
 impl Day for Day22 {
@@ -443,6 +447,76 @@ impl Day for Day22 {
         separated_pair(parse_grid, count(line_ending, 2), parse_sequence)(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        let grid = input
+            .0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| match tile {
+                        Tile::Out => ' ',
+                        Tile::Free => '.',
+                        Tile::Wall => '#',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n");
+        let sequence = input
+            .1
+            .iter()
+            .map(|instr| match instr {
+                Instruction::Walk(dist) => dist.to_string(),
+                Instruction::RotateLeft => "L".to_string(),
+                Instruction::RotateRight => "R".to_string(),
+            })
+            .join("");
+        format!("{grid}\n\n{sequence}")
+    }
+
+    /// The solver expects the faces of the cube to be laid out like in the real puzzle input, so `scale` only gives
+    /// the length of the path
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        // columns of the faces in each band of rows, in units of the cube size
+        let mut grid = vec![];
+        // each band of rows has faces in a range of columns, in units of the cube size
+        for faces in [1..3, 1..2, 0..2, 0..1] {
+            for _ in 0..CUBE_SIZE {
+                let row = (0..faces.end * CUBE_SIZE)
+                    .map(|x| match x {
+                        _ if x < faces.start * CUBE_SIZE => Tile::Out,
+                        _ if rng.gen_bool(0.1) => Tile::Wall,
+                        _ => Tile::Free,
+                    })
+                    .collect_vec();
+                grid.push(row);
+            }
+        }
+        let mut sequence = vec![];
+        for _ in 0..2000 * scale {
+            sequence.push(Instruction::Walk(rng.gen_range(1..=50)));
+            sequence.push(if rng.gen_bool(0.5) {
+                Instruction::RotateLeft
+            } else {
+                Instruction::RotateRight
+            });
+        }
+        sequence.push(Instruction::Walk(rng.gen_range(1..=50)));
+        (grid, sequence)
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let (grid, instructions) = input;
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+        let tiles = grid.iter().flatten().filter(|&t| *t != Tile::Out).count();
+        let side = ((tiles / 6) as f64).sqrt() as usize;
+        vec![
+            ("map", format!("{width}x{}", grid.len())),
+            ("instructions", instructions.len().to_string()),
+            ("cube side", side.to_string()),
+            ("cube net", cube_net(grid, side)),
+        ]
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 1.4419ms
@@ -455,12 +529,12 @@ impl Day for Day22 {
                 .position(|t| matches!(t, Tile::Free))
                 .unwrap(),
             y: 0,
-            dir: Dir::Right,
+            dir: Direction::Right,
         };
         for i in instr {
             player.perform(i, grid, false);
         }
-        1000 * (player.y + 1) + 4 * (player.x + 1) + isize::from(&player.dir) as usize
+        1000 * (player.y + 1) + 4 * (player.x + 1) + facing(player.dir)
     }
 
     type Output2 = usize;
@@ -475,13 +549,76 @@ impl Day for Day22 {
                 .position(|t| matches!(t, Tile::Free))
                 .unwrap(),
             y: 0,
-            dir: Dir::Right,
+            dir: Direction::Right,
         };
         for i in instr {
             player.perform(i, grid, true);
             /* print_cube_face(player.x, player.y, &player.dir, grid);
             std::thread::sleep(std::time::Duration::from_secs(1)); */
         }
-        1000 * (player.y + 1) + 4 * (player.x + 1) + isize::from(&player.dir) as usize
+        1000 * (player.y + 1) + 4 * (player.x + 1) + facing(player.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    fn tile() -> impl Strategy<Value = Tile> {
+        prop_oneof![Just(Tile::Out), Just(Tile::Free), Just(Tile::Wall)]
+    }
+
+    /// Walks alternate with turns, otherwise two consecutive walks would be read as one
+    fn sequence() -> impl Strategy<Value = Vec<Instruction>> {
+        (
+            prop::collection::vec((any::<u16>(), any::<bool>()), 0..20),
+            any::<u16>(),
+        )
+            .prop_map(|(steps, last)| {
+                let mut sequence = Vec::new();
+                for (dist, left) in steps {
+                    sequence.push(Instruction::Walk(dist as usize));
+                    sequence.push(match left {
+                        true => Instruction::RotateLeft,
+                        false => Instruction::RotateRight,
+                    });
+                }
+                sequence.push(Instruction::Walk(last as usize));
+                sequence
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip(
+            grid in prop::collection::vec(prop::collection::vec(tile(), 1..20), 1..20),
+            sequence in sequence(),
+        ) {
+            assert_roundtrip::<Day22>(&(grid, sequence));
+        }
+    }
+
+    #[test]
+    fn net() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let (grid, _) = Day22::generate(&mut StdRng::seed_from_u64(22), 1);
+        assert_eq!(cube_net(&grid, CUBE_SIZE), ".12\n.3.\n45.\n6..");
+        // a net of side 2 laid out differently
+        let grid = ["..", "..", "......", "......", "  ..", "  .."]
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| match c {
+                        '.' => Tile::Free,
+                        _ => Tile::Out,
+                    })
+                    .collect()
+            })
+            .collect_vec();
+        assert_eq!(cube_net(&grid, 2), "1..\n234\n.5.");
     }
 }
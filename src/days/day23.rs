@@ -6,24 +6,25 @@ use nom::{
     multi::{many1, separated_list0},
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
+use crate::{
+    days::Day,
+    geom::{self, Vector},
+};
 
-type Offset = (i64, i64);
+type Offset = Vector<i64>;
 
 /// North - South - West - East
 const DIRS: [[Offset; 3]; 4] = [
-    [(-1, -1), (0, -1), (1, -1)], // NW, N, NE
-    [(-1, 1), (0, 1), (1, 1)],    // SW, S, SE
-    [(-1, -1), (-1, 0), (-1, 1)], // NW, W, SW
-    [(1, -1), (1, 0), (1, 1)],    // NE, E, SE
+    [Offset::new(-1, -1), Offset::new(0, -1), Offset::new(1, -1)], // NW, N, NE
+    [Offset::new(-1, 1), Offset::new(0, 1), Offset::new(1, 1)],    // SW, S, SE
+    [Offset::new(-1, -1), Offset::new(-1, 0), Offset::new(-1, 1)], // NW, W, SW
+    [Offset::new(1, -1), Offset::new(1, 0), Offset::new(1, 1)],    // NE, E, SE
 ];
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Point {
-    pub x: i64,
-    pub y: i64,
-}
+/// x points to the right, y points to the bottom
+type Point = geom::Point<i64>;
 
 /// alias for Point to convey there is an elf currently in this position
 type ElfPosition = Point;
@@ -33,9 +34,8 @@ type Elves = HashSet<ElfPosition>;
 
 trait Searchable {
     fn get_elves_area(&self) -> usize;
-    fn has_elf_at_pos(&self, x: i64, y: i64) -> bool;
-    fn has_elf_around(&self, x: i64, y: i64) -> bool;
-    fn has_elf_on_side(&self, x: i64, y: i64, offsets: &[Offset; 3]) -> bool;
+    fn has_elf_around(&self, pos: &ElfPosition) -> bool;
+    fn has_elf_on_side(&self, pos: &ElfPosition, offsets: &[Offset; 3]) -> bool;
 }
 
 impl Searchable for Elves {
@@ -50,26 +50,19 @@ impl Searchable for Elves {
         ((max_x.x - min_x.x + 1) * (max_y.y - min_y.y + 1)) as usize
     }
 
-    /// Check if there is an elf at x, y
-    fn has_elf_at_pos(&self, x: i64, y: i64) -> bool {
-        self.contains(&ElfPosition { x, y })
-    }
-
     /// Check if there is any elf around
-    fn has_elf_around(&self, x: i64, y: i64) -> bool {
-        DIRS.iter()
-            .flatten()
-            .any(|d| self.has_elf_at_pos(x + d.0, y + d.1))
+    fn has_elf_around(&self, pos: &ElfPosition) -> bool {
+        Offset::ALL_AROUND
+            .iter()
+            .any(|d| self.contains(&(*pos + *d)))
     }
 
     /// Check if there is an elf in the top or bottom or left or right side
     ///
     /// offsets are the 3 cardinal directions of the considered side.
     /// e.g. for the top it would be : N, NW, NE
-    fn has_elf_on_side(&self, x: i64, y: i64, offsets: &[Offset; 3]) -> bool {
-        offsets
-            .iter()
-            .any(|d| self.has_elf_at_pos(x + d.0, y + d.1))
+    fn has_elf_on_side(&self, pos: &ElfPosition, offsets: &[Offset; 3]) -> bool {
+        offsets.iter().any(|d| self.contains(&(*pos + *d)))
     }
 }
 
@@ -81,21 +74,17 @@ fn move_elves(elves: &mut Elves, dir_counter: usize) -> bool {
     let mut has_moved = false;
     for elf in elves.iter() {
         // in case there are no elves around, the elf doesn't move
-        if !elves.has_elf_around(elf.x, elf.y) {
+        if !elves.has_elf_around(elf) {
             continue;
         }
         // try each of the 4 directions, starting with dir_counter (mod 4)
         for i in 0..4 {
             let dirs = DIRS[(dir_counter + i) % 4];
             // check if there are any elves in that direction
-            if !elves.has_elf_on_side(elf.x, elf.y, &dirs) {
+            if !elves.has_elf_on_side(elf, &dirs) {
                 // only if there are no elves in the 3 tiles on that side, we
-                // propose a move at elf.pos.x + dirs[1].0, elf.pos.y + dirs[1].1
-                let next = Point {
-                    x: elf.x + dirs[1].0,
-                    y: elf.y + dirs[1].1,
-                };
-                moves.push((next, elf.clone()));
+                // propose a move in the middle direction of that side
+                moves.push((*elf + dirs[1], *elf));
                 break;
             }
         }
@@ -139,6 +128,45 @@ impl Day for Day23 {
         Ok((rest, elves))
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        // parsed positions are never negative, so the grid starts at 0, 0
+        let width = input.iter().map(|p| p.x + 1).max().unwrap_or(0);
+        let height = input.iter().map(|p| p.y + 1).max().unwrap_or(0);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match input.contains(&ElfPosition { x, y }) {
+                        true => '#',
+                        false => '.',
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        let size = 73 * scale as i64;
+        (0..size)
+            .cartesian_product(0..size)
+            .filter(|_| rng.gen_bool(0.5))
+            .map(|(x, y)| ElfPosition { x, y })
+            .collect()
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        let mut stats = vec![("elves", input.len().to_string())];
+        if let (MinMax(min_x, max_x), MinMax(min_y, max_y)) = (
+            input.iter().map(|p| p.x).minmax(),
+            input.iter().map(|p| p.y).minmax(),
+        ) {
+            stats.push((
+                "grid",
+                format!("{}x{}", max_x - min_x + 1, max_y - min_y + 1),
+            ));
+        }
+        stats
+    }
+
     type Output1 = usize;
 
     /// Part 1 took 4.1408ms
@@ -168,3 +196,21 @@ impl Day for Day23 {
         dir_counter + 1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::hash_set(
+            (0..20i64, 0..20i64).prop_map(|(x, y)| ElfPosition { x, y }),
+            0..50,
+        )) {
+            assert_roundtrip::<Day23>(&input);
+        }
+    }
+}
@@ -1,45 +1,32 @@
 use std::{
     cmp::Ordering,
     collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    iter::once,
 };
 
+use itertools::Itertools;
+
 use nom::{
     character::complete::{line_ending, one_of},
+    combinator::verify,
     multi::{many1, separated_list0},
     IResult,
 };
+use rand::Rng;
 
-use crate::days::Day;
-
-/// Top - Right - Bottom - Left
-const DIRS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+use crate::{
+    days::Day,
+    geom::{self, Direction},
+};
 
 const WIDTH: usize = 150;
 const HEIGHT: usize = 20;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Point {
-    x: isize,
-    y: isize,
-}
-
-impl Point {
-    /// Calculate the Manhattan distance to another point
-    fn distance_to(&self, other: &Point) -> isize {
-        (self.x.max(other.x) - self.x.min(other.x)) + (self.y.max(other.y) - self.y.min(other.y))
-    }
-
-    /// Get a new point in the direction passed as argument
-    fn at_dir(&self, dir: (isize, isize)) -> Point {
-        Point {
-            x: self.x + dir.0,
-            y: self.y + dir.1,
-        }
-    }
-}
+/// x points to the right, y points to the bottom
+type Point = geom::Point<isize>;
 
 /// A blizzard instance, with its starting coordinates as the value
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Blizzard {
     Up(Point),
     Right(Point),
@@ -87,7 +74,7 @@ fn blizz_at_ts<'a>(
     blizz_cache.last().unwrap()
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Game {
     blizz: Vec<Blizzard>,
     start: Point,
@@ -113,13 +100,13 @@ impl PartialEq for OpenPos {
 
 impl PartialOrd for OpenPos {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        other.cost.partial_cmp(&self.cost)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for OpenPos {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        other.cost.cmp(&self.cost)
     }
 }
 
@@ -135,8 +122,8 @@ impl OpenPos {
     ) -> Vec<Point> {
         let mut n = Vec::<Point>::with_capacity(4);
         let blizz = blizz_at_ts(blizz, blizz_cache, timestep);
-        for dir in DIRS {
-            let next = self.point.at_dir(dir);
+        for dir in Direction::ALL {
+            let next = self.point.step(dir);
             // exclude out of bounds (except for start and end points)
             if (next.y < 0 && next.x != start_x)
                 || (next.y >= HEIGHT as isize && next.x != end_x)
@@ -153,7 +140,7 @@ impl OpenPos {
         }
         // we can also wait in place
         if !blizz.contains(&self.point) {
-            n.push(self.point.clone());
+            n.push(self.point);
         }
         n
     }
@@ -165,11 +152,11 @@ fn path(
     current: (Point, usize),
 ) -> VecDeque<(Point, usize)> {
     let mut path: VecDeque<(Point, usize)> = VecDeque::new();
-    path.push_front(current.clone());
+    path.push_front(current);
     let mut current = current;
     while came_from.contains_key(&current) {
-        current = came_from.get(&current).unwrap().clone();
-        path.push_front(current.clone())
+        current = *came_from.get(&current).unwrap();
+        path.push_front(current)
     }
     path
 }
@@ -184,15 +171,15 @@ fn a_star(
 ) -> Option<usize> {
     let mut open_set = BinaryHeap::<OpenPos>::new();
     open_set.push(OpenPos {
-        point: start.clone(),
+        point: *start,
         timestep: start_timestep,
-        cost: start_timestep as isize + start.distance_to(end), // f-score, which is the expected cost to reach the End node
+        cost: start_timestep as isize + start.manhattan(end), // f-score, which is the expected cost to reach the End node
     });
     // this map stores the path dependencies so we can reconstruct it later
     let mut came_from = HashMap::<(Point, usize), (Point, usize)>::new();
     // this map stores the g-score (quantity to minimize: the elapsed time) for each visited Point
     let mut g_score = HashMap::<(Point, usize), isize>::new();
-    g_score.insert((start.clone(), start_timestep), start_timestep as isize);
+    g_score.insert((*start, start_timestep), start_timestep as isize);
 
     while let Some(current) = open_set.pop() {
         if current.point == *end {
@@ -210,20 +197,20 @@ fn a_star(
             )
             .iter()
         {
-            let tentative_gscore = g_score[&(current.point.clone(), current.timestep)] + 1;
+            let tentative_gscore = g_score[&(current.point, current.timestep)] + 1;
             if tentative_gscore
                 < *g_score
-                    .get(&(n.clone(), current.timestep + 1))
+                    .get(&(*n, current.timestep + 1))
                     .unwrap_or(&isize::MAX)
             {
                 came_from.insert(
-                    (n.clone(), current.timestep + 1),
-                    (current.point.clone(), current.timestep),
+                    (*n, current.timestep + 1),
+                    (current.point, current.timestep),
                 );
-                g_score.insert((n.clone(), current.timestep + 1), tentative_gscore);
+                g_score.insert((*n, current.timestep + 1), tentative_gscore);
                 let pos = OpenPos {
-                    point: n.clone(),
-                    cost: tentative_gscore + n.distance_to(end), // f-score
+                    point: *n,
+                    cost: tentative_gscore + n.manhattan(end), // f-score
                     timestep: current.timestep + 1,
                 };
                 // since we want to replace this point in the min-heap if it exists, we need to remove it first
@@ -241,7 +228,11 @@ impl Day for Day24 {
     type Input = Game;
 
     fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, positions) = separated_list0(line_ending, many1(one_of(".#^>v<")))(input)?;
+        // each row starts and ends with a wall
+        let row = verify(many1(one_of(".#^>v<")), |row: &Vec<char>| {
+            row.first() == Some(&'#') && row.last() == Some(&'#')
+        });
+        let (rest, positions) = separated_list0(line_ending, row)(input)?;
         let mut blizz = Vec::with_capacity(WIDTH * HEIGHT);
         let mut start_x = 0;
         let mut end_x = 0;
@@ -288,6 +279,70 @@ impl Day for Day24 {
         ))
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        let mut grid = vec![vec!['.'; WIDTH]; HEIGHT];
+        for b in &input.blizz {
+            let (pos, c) = match b {
+                Blizzard::Up(pos) => (pos, '^'),
+                Blizzard::Right(pos) => (pos, '>'),
+                Blizzard::Down(pos) => (pos, 'v'),
+                Blizzard::Left(pos) => (pos, '<'),
+            };
+            grid[pos.y as usize][pos.x as usize] = c;
+        }
+        // the top and bottom walls have a single opening, at the start and end positions respectively
+        let wall = |opening: isize| {
+            (-1..=WIDTH as isize)
+                .map(|x| if x == opening { '.' } else { '#' })
+                .collect::<String>()
+        };
+        let rows = grid
+            .iter()
+            .map(|row| format!("#{}#", row.iter().collect::<String>()));
+        once(wall(input.start.x))
+            .chain(rows)
+            .chain(once(wall(input.end.x)))
+            .join("\n")
+    }
+
+    /// The valley must fit in the grid of the solver, so `scale` is ignored
+    ///
+    /// Like in the real puzzle input, the start and end columns have no vertical blizzards, which would otherwise
+    /// go through the openings in the walls.
+    fn generate<R: Rng>(rng: &mut R, _scale: usize) -> Self::Input {
+        let (start_x, end_x) = (0, WIDTH as isize - 1);
+        let mut blizz = vec![];
+        for (y, x) in (0..HEIGHT as isize).cartesian_product(0..WIDTH as isize) {
+            if !rng.gen_bool(0.5) {
+                continue;
+            }
+            let pos = Point { x, y };
+            let horizontal = x == start_x || x == end_x || rng.gen_bool(0.5);
+            blizz.push(match (horizontal, rng.gen_bool(0.5)) {
+                (true, true) => Blizzard::Right(pos),
+                (true, false) => Blizzard::Left(pos),
+                (false, true) => Blizzard::Down(pos),
+                (false, false) => Blizzard::Up(pos),
+            });
+        }
+        Game {
+            blizz,
+            start: Point { x: start_x, y: -1 },
+            end: Point {
+                x: end_x,
+                y: HEIGHT as isize,
+            },
+        }
+    }
+
+    fn describe(input: &Self::Input) -> Vec<(&'static str, String)> {
+        vec![
+            // the size is fixed, as the solver assumes it
+            ("valley", format!("{WIDTH}x{HEIGHT}")),
+            ("blizzards", input.blizz.len().to_string()),
+        ]
+    }
+
     type Output1 = usize;
 
     fn part_1(input: &Self::Input) -> Self::Output1 {
@@ -319,3 +374,42 @@ impl Day for Day24 {
         first + second + third
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::days::assert_roundtrip;
+
+    proptest! {
+        #[test]
+        fn roundtrip(
+            cells in prop::collection::vec(prop::sample::select(vec!['.', '^', '>', 'v', '<']), WIDTH * HEIGHT),
+            start_x in 0..WIDTH as isize,
+            end_x in 0..WIDTH as isize,
+        ) {
+            // blizzards are listed in the order they appear in the grid, like when parsing
+            let blizz = cells
+                .iter()
+                .enumerate()
+                .filter_map(|(i, c)| {
+                    let pos = Point::new((i % WIDTH) as isize, (i / WIDTH) as isize);
+                    match c {
+                        '^' => Some(Blizzard::Up(pos)),
+                        '>' => Some(Blizzard::Right(pos)),
+                        'v' => Some(Blizzard::Down(pos)),
+                        '<' => Some(Blizzard::Left(pos)),
+                        _ => None,
+                    }
+                })
+                .collect();
+            let input = Game {
+                blizz,
+                start: Point::new(start_x, -1),
+                end: Point::new(end_x, HEIGHT as isize),
+            };
+            assert_roundtrip::<Day24>(&input);
+        }
+    }
+}
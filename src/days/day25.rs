@@ -1,11 +1,13 @@
 use itertools::Itertools;
 use nom::{
-    character::complete::{line_ending, not_line_ending},
+    character::complete::{line_ending, one_of},
     combinator::map,
-    multi::separated_list0,
+    multi::{many1, separated_list1},
     IResult,
 };
+use rand::Rng;
 
+use crate::answer::Answer;
 use crate::days::Day;
 
 /// Convert a snafu into decimal
@@ -50,53 +52,72 @@ fn decimal_to_snafu(input: u64) -> Vec<i8> {
     out.into_iter().rev().skip_while(|&d| d == 0).collect_vec()
 }
 
+/// Convert the -2 to +2 digits of a snafu into characters and collect them into a string
+fn snafu_to_string(input: &[i8]) -> String {
+    String::from_iter(input.iter().map(|d| match d {
+        -2 => '=',
+        -1 => '-',
+        0 => '0',
+        1 => '1',
+        2 => '2',
+        _ => unreachable!(),
+    }))
+}
+
 pub struct Day25;
 
 impl Day for Day25 {
     type Input = Vec<Vec<i8>>;
 
     fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
+        separated_list1(
             line_ending,
-            map(not_line_ending, |s: &str| {
-                s.chars()
-                    .map(|c| match c {
-                        '-' => -1,
-                        '=' => -2,
-                        d => ((d as u8) - 48) as i8,
-                    })
-                    .collect_vec()
-            }),
+            many1(map(one_of("=-012"), |c| match c {
+                '-' => -1,
+                '=' => -2,
+                d => ((d as u8) - 48) as i8,
+            })),
         )(input)
     }
 
+    fn unparse(input: &Self::Input) -> String {
+        input.iter().map(|s| snafu_to_string(s)).join("\n")
+    }
+
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input {
+        (0..120 * scale)
+            .map(|_| {
+                // the most significant digit is positive, so that the sum is positive too
+                let mut snafu = vec![rng.gen_range(1..=2)];
+                snafu.extend((1..rng.gen_range(1..=20)).map(|_| rng.gen_range(-2..=2)));
+                snafu
+            })
+            .collect()
+    }
+
     type Output1 = String;
 
     /// Part 1 took 0.016806ms
     fn part_1(input: &Self::Input) -> Self::Output1 {
         let sum = input.iter().map(|s| snafu_to_decimal(s)).sum::<i64>();
         let snafu = decimal_to_snafu(sum as u64);
-        // convert -2 to +2 into characters and collect into a string
-        String::from_iter(snafu.into_iter().map(|d| match d {
-            -2 => '=',
-            -1 => '-',
-            0 => '0',
-            1 => '1',
-            2 => '2',
-            _ => unreachable!(),
-        }))
+        snafu_to_string(&snafu)
     }
 
-    type Output2 = String;
+    type Output2 = Answer;
 
     fn part_2(_input: &Self::Input) -> Self::Output2 {
-        "Congratulations!".to_string()
+        // there is no puzzle, the second star is given for all the others
+        Answer::None
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::days::assert_roundtrip;
 
     #[test]
     fn to_decimal() {
@@ -108,4 +129,11 @@ mod tests {
         assert_eq!(decimal_to_snafu(2022), vec![1, -2, 1, 1, -1, 2]);
         assert_eq!(decimal_to_snafu(12345), vec![1, -1, 0, -1, -1, -1, 0]);
     }
+
+    proptest! {
+        #[test]
+        fn roundtrip(input in prop::collection::vec(prop::collection::vec(-2i8..=2, 1..20), 1..20)) {
+            assert_roundtrip::<Day25>(&input);
+        }
+    }
 }
@@ -1,72 +1,549 @@
+use std::borrow::Cow;
+
+use crate::answer::Answer;
+use crate::cache::{Cache, Entry};
+use crate::inputs;
+use crate::logging;
 use crate::parser::MyErr;
 use crate::Instant;
 use nom::IResult;
-use std::fmt::Display;
-use std::fs::read_to_string;
+use rand::Rng;
+use tracing::info_span;
 
+#[cfg(feature = "day01")]
 pub mod day01;
+#[cfg(feature = "day02")]
 pub mod day02;
+#[cfg(feature = "day03")]
 pub mod day03;
+#[cfg(feature = "day04")]
 pub mod day04;
+#[cfg(feature = "day05")]
 pub mod day05;
+#[cfg(feature = "day06")]
 pub mod day06;
+#[cfg(feature = "day07")]
 pub mod day07;
+#[cfg(feature = "day08")]
 pub mod day08;
+#[cfg(feature = "day09")]
 pub mod day09;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day18")]
 pub mod day18;
+#[cfg(feature = "day19")]
 pub mod day19;
+#[cfg(feature = "day20")]
 pub mod day20;
+#[cfg(feature = "day21")]
 pub mod day21;
+#[cfg(feature = "day22")]
 pub mod day22;
+#[cfg(feature = "day23")]
 pub mod day23;
+#[cfg(feature = "day24")]
 pub mod day24;
+#[cfg(feature = "day25")]
 pub mod day25;
 
+/// Whether each day was compiled in, with its `dayNN` feature
+const COMPILED: [bool; 25] = [
+    cfg!(feature = "day01"),
+    cfg!(feature = "day02"),
+    cfg!(feature = "day03"),
+    cfg!(feature = "day04"),
+    cfg!(feature = "day05"),
+    cfg!(feature = "day06"),
+    cfg!(feature = "day07"),
+    cfg!(feature = "day08"),
+    cfg!(feature = "day09"),
+    cfg!(feature = "day10"),
+    cfg!(feature = "day11"),
+    cfg!(feature = "day12"),
+    cfg!(feature = "day13"),
+    cfg!(feature = "day14"),
+    cfg!(feature = "day15"),
+    cfg!(feature = "day16"),
+    cfg!(feature = "day17"),
+    cfg!(feature = "day18"),
+    cfg!(feature = "day19"),
+    cfg!(feature = "day20"),
+    cfg!(feature = "day21"),
+    cfg!(feature = "day22"),
+    cfg!(feature = "day23"),
+    cfg!(feature = "day24"),
+    cfg!(feature = "day25"),
+];
+
+/// Whether the given day was compiled in, which is the case of all the days by default
+pub fn compiled(day: usize) -> bool {
+    (1..=25).contains(&day) && COMPILED[day - 1]
+}
+
+/// The days which were left out of the build, from the 1st to the 25th
+pub fn compiled_out() -> Vec<usize> {
+    (1..=25).filter(|&day| !compiled(day)).collect()
+}
+
+/// Evaluate an expression with `$d` being the `Day` implementation for the given day number
+///
+/// Panics if the day is outside the range of 1 to 25, or was not compiled in.
+macro_rules! with_day {
+    ($day:expr, $d:ident => $body:expr) => {
+        match $day {
+            #[cfg(feature = "day01")]
+            1 => {
+                type $d = $crate::days::day01::Day01;
+                $body
+            }
+            #[cfg(feature = "day02")]
+            2 => {
+                type $d = $crate::days::day02::Day02;
+                $body
+            }
+            #[cfg(feature = "day03")]
+            3 => {
+                type $d = $crate::days::day03::Day03;
+                $body
+            }
+            #[cfg(feature = "day04")]
+            4 => {
+                type $d = $crate::days::day04::Day04;
+                $body
+            }
+            #[cfg(feature = "day05")]
+            5 => {
+                type $d = $crate::days::day05::Day05;
+                $body
+            }
+            #[cfg(feature = "day06")]
+            6 => {
+                type $d = $crate::days::day06::Day06;
+                $body
+            }
+            #[cfg(feature = "day07")]
+            7 => {
+                type $d = $crate::days::day07::Day07;
+                $body
+            }
+            #[cfg(feature = "day08")]
+            8 => {
+                type $d = $crate::days::day08::Day08;
+                $body
+            }
+            #[cfg(feature = "day09")]
+            9 => {
+                type $d = $crate::days::day09::Day09;
+                $body
+            }
+            #[cfg(feature = "day10")]
+            10 => {
+                type $d = $crate::days::day10::Day10;
+                $body
+            }
+            #[cfg(feature = "day11")]
+            11 => {
+                type $d = $crate::days::day11::Day11;
+                $body
+            }
+            #[cfg(feature = "day12")]
+            12 => {
+                type $d = $crate::days::day12::Day12;
+                $body
+            }
+            #[cfg(feature = "day13")]
+            13 => {
+                type $d = $crate::days::day13::Day13;
+                $body
+            }
+            #[cfg(feature = "day14")]
+            14 => {
+                type $d = $crate::days::day14::Day14;
+                $body
+            }
+            #[cfg(feature = "day15")]
+            15 => {
+                type $d = $crate::days::day15::Day15;
+                $body
+            }
+            #[cfg(feature = "day16")]
+            16 => {
+                type $d = $crate::days::day16::Day16;
+                $body
+            }
+            #[cfg(feature = "day17")]
+            17 => {
+                type $d = $crate::days::day17::Day17;
+                $body
+            }
+            #[cfg(feature = "day18")]
+            18 => {
+                type $d = $crate::days::day18::Day18;
+                $body
+            }
+            #[cfg(feature = "day19")]
+            19 => {
+                type $d = $crate::days::day19::Day19;
+                $body
+            }
+            #[cfg(feature = "day20")]
+            20 => {
+                type $d = $crate::days::day20::Day20;
+                $body
+            }
+            #[cfg(feature = "day21")]
+            21 => {
+                type $d = $crate::days::day21::Day21;
+                $body
+            }
+            #[cfg(feature = "day22")]
+            22 => {
+                type $d = $crate::days::day22::Day22;
+                $body
+            }
+            #[cfg(feature = "day23")]
+            23 => {
+                type $d = $crate::days::day23::Day23;
+                $body
+            }
+            #[cfg(feature = "day24")]
+            24 => {
+                type $d = $crate::days::day24::Day24;
+                $body
+            }
+            #[cfg(feature = "day25")]
+            25 => {
+                type $d = $crate::days::day25::Day25;
+                $body
+            }
+            d => panic!("Provided unsupported day {d}, which may have been compiled out"),
+        }
+    };
+}
+pub(crate) use with_day;
+
+/// The input as the parsers expect it, with `\n` line endings and without a trailing newline
+///
+/// Inputs saved on Windows have `\r\n` line endings, and most files end with a newline, which the parsers would
+/// otherwise stop at or read as an extra empty item.
+pub fn normalize(text: &str) -> Cow<'_, str> {
+    let text = text.trim_end_matches(['\r', '\n']);
+    match text.contains('\r') {
+        true => Cow::Owned(text.replace("\r\n", "\n")),
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// An alternative solver of a part, with its name
+pub type Variant<I, O> = (&'static str, fn(&I) -> O);
+
+/// Name of the main solver of each part, which the variants are compared to
+pub const DEFAULT_VARIANT: &str = "default";
+
 pub trait Day {
     type Input;
 
     fn parse(input_string: &str) -> IResult<&str, Self::Input>;
 
-    type Output1: Display;
+    /// Write the parsed input back in the puzzle's format, so that parsing the result yields the same input
+    fn unparse(input: &Self::Input) -> String;
+
+    /// Generate a random input which the solvers can handle
+    ///
+    /// A `scale` of 1 gives an input of about the size of the real puzzle input.
+    fn generate<R: Rng>(rng: &mut R, scale: usize) -> Self::Input;
+
+    type Output1: Into<Answer>;
 
     fn part_1(input: &Self::Input) -> Self::Output1;
 
-    type Output2: Display;
+    type Output2: Into<Answer>;
 
     fn part_2(input: &Self::Input) -> Self::Output2;
 
+    /// Generate a small random input and compare the solvers with slow but obviously correct reference ones on it
+    ///
+    /// Returns the input along with a description of each disagreement, or `None` if the day has no reference solver.
+    fn differential<R: Rng>(_rng: &mut R) -> Option<(Self::Input, Vec<String>)> {
+        None
+    }
+
+    /// Alternative solvers of part 1, which must give the same answers as `part_1`
+    fn variants_1() -> Vec<Variant<Self::Input, Self::Output1>> {
+        Vec::new()
+    }
+
+    /// Alternative solvers of part 2, which must give the same answers as `part_2`
+    fn variants_2() -> Vec<Variant<Self::Input, Self::Output2>> {
+        Vec::new()
+    }
+
+    /// The names of the solvers of a part, the main one first
+    fn variant_names(part: usize) -> Vec<&'static str> {
+        let variants = match part {
+            1 => Self::variants_1()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect(),
+            _ => Self::variants_2()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+        };
+        std::iter::once(DEFAULT_VARIANT).chain(variants).collect()
+    }
+
+    /// Solve a part with the solver of the given name, if it exists
+    fn solve(input: &Self::Input, part: usize, variant: &str) -> Option<Answer> {
+        match (part, variant) {
+            (1, DEFAULT_VARIANT) => Some(Self::part_1(input).into()),
+            (2, DEFAULT_VARIANT) => Some(Self::part_2(input).into()),
+            (1, variant) => Self::variants_1()
+                .into_iter()
+                .find(|(name, _)| *name == variant)
+                .map(|(_, solve)| solve(input).into()),
+            (2, variant) => Self::variants_2()
+                .into_iter()
+                .find(|(name, _)| *name == variant)
+                .map(|(_, solve)| solve(input).into()),
+            (p, _) => panic!("{p} is not a valid part. Only parts 1 and 2 are allowed."),
+        }
+    }
+
+    /// Statistics about the input which are specific to the day, as (name, value) pairs
+    ///
+    /// They should tell what makes an input harder than another one, like its dimensions or the number of its items.
+    fn describe(_input: &Self::Input) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Extra commands for the REPL, as (usage, description) pairs
+    const REPL_COMMANDS: &'static [(&'static str, &'static str)] = &[];
+
+    /// Run one of the extra REPL commands with its arguments
+    ///
+    /// Returns an error message if the command is unknown or its arguments are invalid.
+    fn repl_command(_input: &Self::Input, command: &str, _args: &[&str]) -> Result<(), String> {
+        Err(format!("Unknown command {command}"))
+    }
+
     fn parse_file(fp: &str) -> Result<Self::Input, MyErr> {
-        let input_string = read_to_string(fp)?;
-        let (_, input) = Self::parse(&input_string)?;
+        let input_string = inputs::read(fp)?;
+        let (_, input) = Self::parse(&normalize(&input_string))?;
         Ok(input)
     }
 
-    fn run_day(fp: &str) {
-        match Self::parse_file(fp) {
-            Err(e) => println!("{e:?}"),
-            Ok(input) => {
-                let day_start = Instant::now();
-                println!("Part 1: {}", Self::part_1(&input));
-                println!(
-                    "Part 1 took {}ms",
-                    day_start.elapsed().as_nanos() as f32 / 1e6
-                );
-                let before2 = Instant::now();
-                println!("Part 2: {}", Self::part_2(&input));
-                println!(
-                    "Part 2 took {}ms",
-                    before2.elapsed().as_nanos() as f32 / 1e6
-                );
-                println!("Day took {}ms", day_start.elapsed().as_nanos() as f32 / 1e6);
+    /// Run both parts, or only the given one, reusing the answers from the cache when possible
+    ///
+    /// The parts which have a variant of the given name are solved with it, the others with their main solver.
+    fn run_day(
+        day: usize,
+        fp: &str,
+        part: Option<usize>,
+        variant: Option<&str>,
+        cache: &mut Cache,
+    ) {
+        let _day = info_span!("day", day).entered();
+        let input_string = match inputs::read(fp) {
+            Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+            Ok(input_string) => input_string,
+        };
+        // the same input with other line endings or trailing newlines shares its cache entries
+        let input_string = normalize(&input_string);
+        let parts = match part {
+            Some(part) => vec![part],
+            None => vec![1, 2],
+        };
+        let solvers = parts
+            .iter()
+            .map(|&part| match variant {
+                Some(variant) if Self::variant_names(part).contains(&variant) => (part, variant),
+                _ => (part, DEFAULT_VARIANT),
+            })
+            .collect::<Vec<_>>();
+        let cached = solvers
+            .into_iter()
+            .map(|(part, name)| {
+                let key = Cache::key(day, part, name, &input_string);
+                let entry = cache.get(&key).cloned();
+                (part, name, key, entry)
+            })
+            .collect::<Vec<_>>();
+        // the input only needs parsing if one of the parts was not cached
+        let input = if cached.iter().all(|(_, _, _, entry)| entry.is_some()) {
+            None
+        } else {
+            match info_span!("parse").in_scope(|| Self::parse(&input_string)) {
+                Err(e) => {
+                    eprintln!("{}", MyErr::from(e));
+                    return;
+                }
+                Ok((_, input)) => Some(input),
+            }
+        };
+        // the timings are not printed when quiet
+        let timings = !logging::quiet();
+        let day_start = Instant::now();
+        for (part, name, key, cached) in cached {
+            let label = match name {
+                DEFAULT_VARIANT => format!("Part {part}"),
+                name => format!("Part {part} ({name})"),
+            };
+            if let Some(entry) = cached {
+                println!("{}", entry.answer.labelled(&label));
+                if timings {
+                    println!("{label} took {}ms (cached)", entry.time_ms);
+                }
+                continue;
             }
+            let input = input.as_ref().unwrap();
+            let start = Instant::now();
+            let answer = info_span!("part", part, variant = name)
+                .in_scope(|| Self::solve(input, part, name).unwrap());
+            let time_ms = start.elapsed().as_nanos() as f32 / 1e6;
+            println!("{}", answer.labelled(&label));
+            if timings {
+                println!("{label} took {time_ms}ms");
+            }
+            cache.insert(key, Entry { answer, time_ms });
+        }
+        if timings {
+            println!("Day took {}ms", day_start.elapsed().as_nanos() as f32 / 1e6);
+        }
+    }
+}
+
+/// Check that parsing the unparsed input gives back the same input, consuming the whole text
+#[cfg(test)]
+pub fn assert_roundtrip<D: Day>(input: &D::Input)
+where
+    D::Input: PartialEq + std::fmt::Debug,
+{
+    let text = D::unparse(input);
+    let (rest, parsed) = D::parse(&text).expect("unparsed input should be valid");
+    assert!(
+        rest.is_empty(),
+        "unparsed input was not fully consumed: {rest:?}"
+    );
+    assert_eq!(&parsed, input, "input changed after a round-trip:\n{text}");
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// Generated inputs can be written down and parsed back
+    #[test]
+    fn generated_roundtrip() {
+        for day in (1..=25).filter(|&day| compiled(day)) {
+            let mut rng = StdRng::seed_from_u64(day as u64);
+            with_day!(day, D => assert_roundtrip::<D>(&D::generate(&mut rng, 1)));
+        }
+    }
+
+    /// Inputs with Windows line endings or trailing newlines are parsed like the original ones
+    #[test]
+    fn line_endings() {
+        for day in (1..=25).filter(|&day| compiled(day)) {
+            let mut rng = StdRng::seed_from_u64(day as u64);
+            with_day!(day, D => {
+                let input = D::generate(&mut rng, 1);
+                let text = D::unparse(&input);
+                let crlf = text.replace('\n', "\r\n");
+                for variant in [format!("{text}\n"), format!("{text}\n\n"), crlf.clone(), format!("{crlf}\r\n")] {
+                    let normalized = normalize(&variant);
+                    let (rest, parsed) =
+                        D::parse(&normalized).unwrap_or_else(|e| panic!("day {day}: {e}"));
+                    assert!(rest.is_empty(), "day {day} was not fully parsed: {rest:?}");
+                    assert!(parsed == input, "day {day} changed after normalizing {variant:?}");
+                }
+            });
+        }
+    }
+
+    /// The example inputs saved from the puzzles can be parsed entirely
+    #[test]
+    fn examples() {
+        let Ok(entries) = std::fs::read_dir("inputs/examples") else {
+            return;
+        };
+        for entry in entries {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            let Some(day) = name
+                .strip_prefix("day")
+                .and_then(|name| name.get(..2))
+                .and_then(|day| day.parse::<usize>().ok())
+                .filter(|&day| compiled(day))
+            else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path).unwrap();
+            with_day!(day, D => {
+                let text = normalize(&text);
+                let (rest, _) = D::parse(&text).unwrap_or_else(|e| panic!("{name}: {e}"));
+                assert!(rest.is_empty(), "{name} was not fully parsed: {rest:?}");
+            });
+        }
+    }
+
+    /// Solvers agree with the reference solvers on small generated inputs
+    #[test]
+    fn differential() {
+        for day in (1..=25).filter(|&day| compiled(day)) {
+            let mut rng = StdRng::seed_from_u64(day as u64);
+            with_day!(day, D => {
+                for _ in 0..10 {
+                    if let Some((input, disagreements)) = D::differential(&mut rng) {
+                        assert!(
+                            disagreements.is_empty(),
+                            "day {day}: {disagreements:?}\n{}",
+                            D::unparse(&input)
+                        );
+                    }
+                }
+            });
+        }
+    }
+
+    /// Alternative solvers agree with the main ones on generated inputs
+    #[test]
+    fn variants() {
+        for day in (1..=25).filter(|&day| compiled(day)) {
+            let mut rng = StdRng::seed_from_u64(day as u64);
+            with_day!(day, D => {
+                if D::variant_names(1).len() + D::variant_names(2).len() > 2 {
+                    let input = D::generate(&mut rng, 1);
+                    for part in 1..=2 {
+                        let (expected, disagreements) =
+                            crate::variants::disagreements::<D>(&input, part);
+                        assert!(
+                            disagreements.is_empty(),
+                            "day {day} part {part} gives {expected}, but {disagreements:?}"
+                        );
+                    }
+                }
+            });
         }
     }
 }
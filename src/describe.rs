@@ -0,0 +1,60 @@
+//! Statistics about an input, to understand why the solvers are slower on it than on another one
+
+use crate::days::{self, Day};
+use crate::inputs;
+use crate::parser::MyErr;
+
+/// Statistics about the text of the input, which make sense for every day
+fn text_stats(text: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("bytes", text.len().to_string()),
+        ("lines", text.lines().count().to_string()),
+        (
+            "longest line",
+            text.lines()
+                .map(str::len)
+                .max()
+                .unwrap_or_default()
+                .to_string(),
+        ),
+    ]
+}
+
+/// Print the statistics about the text, then the ones specific to the day
+///
+/// The values spanning several lines are printed below their name.
+pub fn print<D: Day>(text: &str, input: &D::Input) {
+    let stats = [text_stats(text), D::describe(input)].concat();
+    let width = stats.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in stats {
+        if value.contains('\n') {
+            println!("{name}:");
+            for line in value.lines() {
+                println!("  {line}");
+            }
+        } else {
+            println!("{:<width$}  {value}", format!("{name}:"), width = width + 1);
+        }
+    }
+}
+
+/// Read and parse an input, then print its statistics
+pub fn describe<D: Day>(fp: &str) -> Result<(), MyErr> {
+    let text = inputs::read(fp)?;
+    let (_, input) = D::parse(&days::normalize(&text))?;
+    print::<D>(&text, &input);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text() {
+        let stats = text_stats("1000\n2000\n\n30000\n");
+        assert_eq!(stats[0], ("bytes", "17".to_string()));
+        assert_eq!(stats[1], ("lines", "4".to_string()));
+        assert_eq!(stats[2], ("longest line", "5".to_string()));
+    }
+}
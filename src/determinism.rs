@@ -0,0 +1,146 @@
+//! Check that the solvers always give the same answer, whatever the number of threads and from one run to the next
+//!
+//! Parallel solvers may find another answer depending on how the work is split between the threads, and the iteration
+//! order of hash maps, which is random for each map, can change how ties are broken.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use colored::Colorize;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPoolBuilder;
+
+use crate::answer::Answer;
+use crate::days::{Day, DEFAULT_VARIANT};
+
+/// The answers of `runs` runs of each part, parsing the input and solving it in a pool of the given number of threads
+///
+/// Each run parses the input again, as the parsed input may hold hash maps with their own iteration order. Fails if
+/// the input can't be parsed or a solver panics, telling which part and thread count.
+fn answers<D: Day>(fp: &str, threads: usize, runs: usize) -> Result<[Vec<Answer>; 2], String> {
+    let run = || {
+        let mut answers = [Vec::with_capacity(runs), Vec::with_capacity(runs)];
+        for (part, answers) in (1..).zip(&mut answers) {
+            for _ in 0..runs {
+                let input = D::parse_file(fp).map_err(|e| e.to_string())?;
+                // the panic message is printed by the hook, and the other days are still checked
+                let answer = panic::catch_unwind(AssertUnwindSafe(|| {
+                    D::solve(&input, part, DEFAULT_VARIANT)
+                }))
+                .map_err(|_| format!("Part {part} panicked with {threads} threads"))?
+                .ok_or(format!("Part {part} has no {DEFAULT_VARIANT} solver"))?;
+                answers.push(answer);
+            }
+        }
+        Ok(answers)
+    };
+    #[cfg(feature = "parallel")]
+    {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| format!("Could not create the thread pool: {e}"))?;
+        // the input is parsed in the pool too, as it may not be shareable between threads
+        pool.install(run)
+    }
+    // without the parallel days, there is no pool and the solvers only use the current thread
+    #[cfg(not(feature = "parallel"))]
+    run()
+}
+
+/// The distinct answers, in the order they were first given, with how many runs gave each one for each thread count
+fn tally(answers: &[(usize, Vec<Answer>)]) -> Vec<(&Answer, Vec<(usize, usize)>)> {
+    let mut tally: Vec<(&Answer, Vec<(usize, usize)>)> = Vec::new();
+    for (threads, runs) in answers {
+        for answer in runs {
+            let i = match tally.iter().position(|(a, _)| *a == answer) {
+                Some(i) => i,
+                None => {
+                    tally.push((answer, Vec::new()));
+                    tally.len() - 1
+                }
+            };
+            match tally[i].1.iter_mut().find(|(t, _)| t == threads) {
+                Some((_, count)) => *count += 1,
+                None => tally[i].1.push((*threads, 1)),
+            }
+        }
+    }
+    tally
+}
+
+/// Run both parts `runs` times with one thread, then with `threads` threads if there are more, returning whether the
+/// answers never changed
+///
+/// An input which can't be parsed or a solver which panics fails the check, as the answers could not be compared.
+pub fn check<D: Day>(fp: &str, threads: usize, runs: usize) -> bool {
+    let mut answers = [Vec::new(), Vec::new()];
+    let thread_counts: &[usize] = if threads == 1 { &[1] } else { &[1, threads] };
+    for &threads in thread_counts {
+        match self::answers::<D>(fp, threads, runs) {
+            Err(e) => {
+                eprintln!("{e}");
+                return false;
+            }
+            Ok(parts) => {
+                for (answers, part_answers) in answers.iter_mut().zip(parts) {
+                    answers.push((threads, part_answers));
+                }
+            }
+        }
+    }
+    let mut deterministic = true;
+    for (part, answers) in (1..).zip(answers) {
+        match tally(&answers)[..] {
+            [(answer, _)] => println!(
+                "Part {part}: {} {answer} ({runs} runs with {})",
+                "ok".green(),
+                match threads {
+                    1 => "1 thread".to_string(),
+                    _ => format!("1 and {threads} threads"),
+                }
+            ),
+            ref tally => {
+                deterministic = false;
+                println!("Part {part}: {}", "nondeterministic".red());
+                for (answer, counts) in tally {
+                    let counts = counts
+                        .iter()
+                        .map(|(threads, count)| {
+                            format!("{count}/{runs} runs with {threads} threads")
+                        })
+                        .collect::<Vec<_>>();
+                    println!("  {answer} in {}", counts.join(", "));
+                }
+            }
+        }
+    }
+    deterministic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tallied() {
+        let answers = [
+            (1, vec![Answer::from(3), Answer::from(3)]),
+            (4, vec![Answer::from(3), Answer::from(5)]),
+        ];
+        let tally = tally(&answers);
+        assert_eq!(tally.len(), 2);
+        assert_eq!(tally[0], (&Answer::from(3), vec![(1, 2), (4, 1)]));
+        assert_eq!(tally[1], (&Answer::from(5), vec![(4, 1)]));
+    }
+
+    #[test]
+    #[cfg(feature = "day01")]
+    fn deterministic() {
+        let fp = std::env::temp_dir().join("aoc_2022_determinism_day01.txt");
+        std::fs::write(&fp, "1000\n2000\n\n3000").unwrap();
+        let [part_1, part_2] =
+            answers::<crate::days::day01::Day01>(fp.to_str().unwrap(), 2, 3).unwrap();
+        assert_eq!(part_1, vec![Answer::from(3000); 3]);
+        assert_eq!(part_2, vec![Answer::from(6000); 3]);
+    }
+}
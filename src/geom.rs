@@ -0,0 +1,365 @@
+//! Geometry primitives shared by the grid-based days
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A numeric type that can be used as a coordinate
+pub trait Coord:
+    Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Absolute difference between two values, which also works for unsigned types
+    fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+}
+
+/// A coordinate type that can represent negative offsets
+pub trait SignedCoord: Coord + Neg<Output = Self> {
+    const NEG_ONE: Self;
+
+    /// -1, 0 or 1 depending on the sign of the value
+    fn signum(self) -> Self {
+        match self.cmp(&Self::ZERO) {
+            std::cmp::Ordering::Less => Self::NEG_ONE,
+            std::cmp::Ordering::Equal => Self::ZERO,
+            std::cmp::Ordering::Greater => Self::ONE,
+        }
+    }
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+macro_rules! impl_signed_coord {
+    ($($t:ty),*) => {
+        $(impl SignedCoord for $t {
+            const NEG_ONE: Self = -1;
+        })*
+    };
+}
+
+impl_coord!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+impl_signed_coord!(i8, i16, i32, i64, isize);
+
+/// A position on a 2D grid
+///
+/// Unless stated otherwise in a day's module, x points to the right and y points to the bottom.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A displacement between two `Point`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A position in a 3D grid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// A displacement between two `Point3`s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Vector<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T> Vector3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Coord> Point<T> {
+    /// Manhattan distance to another point
+    pub fn manhattan(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Chebyshev distance to another point (a king's move counts as 1)
+    pub fn chebyshev(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Step once in the given direction, staying within `0..width` and `0..height`
+    ///
+    /// This is useful for unsigned grid coordinates, where stepping outside of the grid would underflow.
+    pub fn checked_step(&self, dir: Direction, width: T, height: T) -> Option<Self> {
+        match dir {
+            Direction::Up => (self.y > T::ZERO).then(|| Self::new(self.x, self.y - T::ONE)),
+            Direction::Right => {
+                (self.x + T::ONE < width).then(|| Self::new(self.x + T::ONE, self.y))
+            }
+            Direction::Down => {
+                (self.y + T::ONE < height).then(|| Self::new(self.x, self.y + T::ONE))
+            }
+            Direction::Left => (self.x > T::ZERO).then(|| Self::new(self.x - T::ONE, self.y)),
+        }
+    }
+}
+
+impl<T: SignedCoord> Point<T> {
+    /// Get the neighboring point in the given direction
+    pub fn step(&self, dir: Direction) -> Self {
+        *self + dir.offset()
+    }
+}
+
+impl<T: SignedCoord> Vector<T> {
+    /// Top - Right - Bottom - Left
+    pub const ORTHOGONAL: [Self; 4] = [
+        Self::new(T::ZERO, T::NEG_ONE),
+        Self::new(T::ONE, T::ZERO),
+        Self::new(T::ZERO, T::ONE),
+        Self::new(T::NEG_ONE, T::ZERO),
+    ];
+
+    /// All 8 neighbors, clockwise starting from the top
+    pub const ALL_AROUND: [Self; 8] = [
+        Self::new(T::ZERO, T::NEG_ONE),
+        Self::new(T::ONE, T::NEG_ONE),
+        Self::new(T::ONE, T::ZERO),
+        Self::new(T::ONE, T::ONE),
+        Self::new(T::ZERO, T::ONE),
+        Self::new(T::NEG_ONE, T::ONE),
+        Self::new(T::NEG_ONE, T::ZERO),
+        Self::new(T::NEG_ONE, T::NEG_ONE),
+    ];
+
+    /// Clamp each component to -1, 0 or 1, to move at most one cell towards the target (diagonals allowed)
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T: SignedCoord> Vector3<T> {
+    /// The 6 face neighbors of a cube: +x, -x, +y, -y, +z, -z
+    pub const FACES: [Self; 6] = [
+        Self::new(T::ONE, T::ZERO, T::ZERO),
+        Self::new(T::NEG_ONE, T::ZERO, T::ZERO),
+        Self::new(T::ZERO, T::ONE, T::ZERO),
+        Self::new(T::ZERO, T::NEG_ONE, T::ZERO),
+        Self::new(T::ZERO, T::ZERO, T::ONE),
+        Self::new(T::ZERO, T::ZERO, T::NEG_ONE),
+    ];
+}
+
+impl<T: Coord> Add<Vector<T>> for Point<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Vector<T>) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coord> AddAssign<Vector<T>> for Point<T> {
+    fn add_assign(&mut self, rhs: Vector<T>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Coord> Sub<Vector<T>> for Point<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Vector<T>) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> SubAssign<Vector<T>> for Point<T> {
+    fn sub_assign(&mut self, rhs: Vector<T>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Vector<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> Add for Vector<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coord> Sub for Vector<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> Mul<T> for Vector<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: SignedCoord> Neg for Vector<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Coord> Add<Vector3<T>> for Point3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Vector3<T>) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Coord> Sub<Vector3<T>> for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Vector3<T>) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Coord> Sub for Point3<T> {
+    type Output = Vector3<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Coord> Add for Vector3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: SignedCoord> Neg for Vector3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// One of the 4 cardinal directions on a grid where y points to the bottom
+///
+/// The variants are ordered clockwise, so that turning right means going to the next variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise starting from the top
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Rotate by 90° clockwise
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Rotate by 90° counter-clockwise
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    /// Unit vector pointing in this direction
+    pub fn offset<T: SignedCoord>(self) -> Vector<T> {
+        Vector::ORTHOGONAL[self as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1usize, 5);
+        let b = Point::new(4usize, 1);
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+    }
+
+    #[test]
+    fn rotations() {
+        assert_eq!(Direction::Left.turn_right(), Direction::Up);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(
+            Point::new(0i64, 0).step(Direction::Right.turn_right()),
+            Point::new(0, 1)
+        );
+    }
+
+    #[test]
+    fn checked_step() {
+        let p = Point::new(0usize, 0);
+        assert_eq!(p.checked_step(Direction::Up, 2, 2), None);
+        assert_eq!(
+            p.checked_step(Direction::Down, 2, 2),
+            Some(Point::new(0, 1))
+        );
+        assert_eq!(
+            Point::new(1usize, 0).checked_step(Direction::Right, 2, 2),
+            None
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Find which days an input belongs to, by parsing it with the parser of each day
+//!
+//! A day accepts an input only when its parser understands all of it, once its line endings are normalized. The other
+//! days are ranked by how far their parser got before giving up.
+
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use colored::Colorize;
+use nom::Err;
+
+use crate::days::{self, with_day, Day};
+
+/// How far the parser of a day got in an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub day: usize,
+    /// Number of bytes understood by the parser
+    pub parsed: usize,
+    /// Whether the parser understood the whole input
+    pub accepted: bool,
+}
+
+thread_local! {
+    /// Whether the panics of the current thread are expected, and should not be printed
+    static SILENT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Install once a panic hook which leaves out the panics of the threads trying the parsers, and hands the other ones
+/// to the previous hook
+///
+/// Swapping the hook around each call instead would silence the panics of the other threads in the meantime, and two
+/// calls at once could leave the silent hook in place.
+fn silence_parser_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !SILENT.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+/// Parse the text with the parser of the day, which counts as not understanding anything when it panics
+fn parse<D: Day>(day: usize, text: &str) -> Match {
+    // some parsers assume a well-formed input and panic otherwise, which should not be printed
+    SILENT.with(|silent| silent.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| D::parse(text).map(|(rest, _)| rest)));
+    SILENT.with(|silent| silent.set(false));
+    let parsed = match result {
+        Ok(Ok("")) => {
+            return Match {
+                day,
+                parsed: text.len(),
+                accepted: true,
+            }
+        }
+        Ok(Ok(rest)) => text.len() - rest.len(),
+        Ok(Err(Err::Error(e) | Err::Failure(e))) => text.len() - e.input.len(),
+        Ok(Err(Err::Incomplete(_))) | Err(_) => 0,
+    };
+    Match {
+        day,
+        parsed,
+        accepted: false,
+    }
+}
+
+/// How far the parser of each compiled day got in the text, the days accepting it first, then the best partial matches
+pub fn identify(text: &str) -> Vec<Match> {
+    let text = &days::normalize(text);
+    silence_parser_panics();
+    let mut matches: Vec<_> = (1..=25)
+        .filter(|&day| days::compiled(day))
+        .map(|day| with_day!(day, D => parse::<D>(day, text)))
+        .collect();
+    matches.sort_by_key(|m| (!m.accepted, text.len() - m.parsed, m.day));
+    matches
+}
+
+/// Print the days accepting the text, or the closest ones, returning whether any day accepted it
+pub fn report(text: &str) -> bool {
+    // the matches are about the normalized text
+    let text = &days::normalize(text);
+    let matches = identify(text);
+    let accepted: Vec<_> = matches.iter().filter(|m| m.accepted).collect();
+    for m in &accepted {
+        println!("Day {}: {}", m.day, "accepted".green());
+    }
+    if accepted.is_empty() {
+        println!(
+            "{}",
+            "No day accepts this input, the closest ones are:".yellow()
+        );
+        for m in matches.iter().filter(|m| m.parsed > 0).take(3) {
+            let line = text[..m.parsed].lines().count().max(1);
+            println!(
+                "Day {}: parsed {:.0}% of the input, up to line {line}",
+                m.day,
+                100. * m.parsed as f32 / text.len() as f32
+            );
+        }
+    }
+    !accepted.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn ranked() {
+        let text = "1000\n2000\n\n3000\n";
+        let matches = identify(text);
+        assert_eq!(matches.len(), 25 - days::compiled_out().len());
+        if days::compiled(1) {
+            assert!(matches.iter().any(|m| m.day == 1 && m.accepted));
+        }
+        // the days accepting the input come first, then the partial matches from the longest one
+        assert!(matches
+            .windows(2)
+            .all(|w| (w[0].accepted, w[0].parsed) >= (w[1].accepted, w[1].parsed)));
+    }
+
+    /// The parsers are strict enough to tell the days apart
+    #[test]
+    fn not_permissive() {
+        for day in (1..=25).filter(|&day| days::compiled(day)) {
+            let mut rng = StdRng::seed_from_u64(day as u64);
+            let text = with_day!(day, D => D::unparse(&D::generate(&mut rng, 1)));
+            let accepted: Vec<_> = identify(&text)
+                .into_iter()
+                .filter(|m| m.accepted)
+                .map(|m| m.day)
+                .collect();
+            // rucksacks are lines of letters, which is also what the signal and the heightmap are made of
+            let expected = match day {
+                6 | 12 if days::compiled(3) => vec![3, day],
+                _ => vec![day],
+            };
+            assert_eq!(accepted, expected, "day {day}");
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Encrypted inputs, so that they can be committed without publishing them
+//!
+//! An input `inputs/dayNN.txt` is stored as `inputs/dayNN.txt.enc`, which holds a random nonce followed by the input
+//! encrypted and authenticated with ChaCha20-Poly1305. The name of the original file is authenticated as well, so that
+//! a renamed encrypted file can't be mistaken for another day's input. The answers files are stored the same way. The
+//! key is read from the `AOC_INPUTS_KEY` environment variable, or else from the `.inputs.key` file, as 64 hexadecimal
+//! digits.
+//!
+//! With the `embed-inputs` feature, the inputs which were available at build time are also part of the binary, and
+//! used when neither file exists.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::{rngs::OsRng, RngCore};
+
+use crate::parser::MyErr;
+
+const KEY_ENV: &str = "AOC_INPUTS_KEY";
+const KEY_FP: &str = ".inputs.key";
+const NONCE_LEN: usize = 12;
+
+/// The embedded inputs by path, as written by the build script
+#[cfg(feature = "embed-inputs")]
+const EMBEDDED: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+
+/// Where the encrypted version of a file is stored
+pub fn encrypted_fp(fp: &str) -> String {
+    format!("{fp}.enc")
+}
+
+fn parse_key(hex_key: &str) -> Result<Key, String> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| format!("invalid key: {e}"))?;
+    if bytes.len() != 32 {
+        return Err(format!("invalid key: {} bytes instead of 32", bytes.len()));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// The key from the environment variable, or else from the key file
+fn load_key() -> Result<Key, String> {
+    match env::var(KEY_ENV) {
+        Ok(hex_key) => parse_key(&hex_key),
+        Err(_) => match fs::read_to_string(KEY_FP) {
+            Ok(hex_key) => parse_key(&hex_key),
+            Err(_) => Err(format!(
+                "no key, set the {KEY_ENV} environment variable or create {KEY_FP} with `inputs keygen`"
+            )),
+        },
+    }
+}
+
+/// Write a new random key to the key file, unless there is one already
+pub fn generate_key() -> Result<(), String> {
+    if Path::new(KEY_FP).exists() {
+        return Err(format!("{KEY_FP} already exists"));
+    }
+    let mut key = [0; 32];
+    OsRng.fill_bytes(&mut key);
+    fs::write(KEY_FP, hex::encode(key)).map_err(|e| e.to_string())
+}
+
+/// The associated data authenticated along with a file: the name of the original file, without its directory
+fn associated_data(fp: &str) -> Vec<u8> {
+    Path::new(fp)
+        .file_name()
+        .map_or_else(|| fp.into(), |name| name.as_encoded_bytes().to_vec())
+}
+
+/// Encrypt the text with a random nonce, which is written before the ciphertext
+fn encrypt(key: &Key, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = ChaCha20Poly1305::new(key)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("encryption cannot fail");
+    [nonce.as_slice(), &ciphertext].concat()
+}
+
+/// Decrypt a blob written by `encrypt`, failing if it was not encrypted with this key and associated data, or was
+/// modified
+fn decrypt(key: &Key, blob: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    if blob.len() < NONCE_LEN {
+        return Err("the file is too short".to_string());
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(key)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| "wrong key, renamed or corrupted file".to_string())
+}
+
+/// Read a file, decrypting its encrypted version if only that one exists
+///
+/// With the `embed-inputs` feature, the embedded input is used when there is neither file.
+pub fn read(fp: &str) -> Result<String, MyErr> {
+    let encrypted = encrypted_fp(fp);
+    #[cfg(feature = "embed-inputs")]
+    if !Path::new(fp).exists() && !Path::new(&encrypted).exists() {
+        return match EMBEDDED
+            .iter()
+            .find(|(path, _)| Path::new(path) == Path::new(fp))
+        {
+            Some((_, input)) => Ok(input.to_string()),
+            None => Err(MyErr::NotEmbedded(fp.to_string())),
+        };
+    }
+    if Path::new(fp).exists() || !Path::new(&encrypted).exists() {
+        return Ok(fs::read_to_string(fp)?);
+    }
+    let blob = fs::read(&encrypted)?;
+    let key = load_key().map_err(MyErr::Decryption)?;
+    let text = decrypt(&key, &blob, &associated_data(fp)).map_err(MyErr::Decryption)?;
+    String::from_utf8(text).map_err(|e| MyErr::Decryption(e.to_string()))
+}
+
+/// What happened to a file when encrypting it
+#[derive(Debug, PartialEq, Eq)]
+pub enum Encrypted {
+    Written,
+    /// The encrypted version already holds the same text, and is kept to avoid rewriting it with a new nonce
+    Unchanged,
+    Missing,
+}
+
+/// Write the encrypted version of a file, unless it already holds the same text
+pub fn encrypt_file(fp: &str) -> Result<Encrypted, String> {
+    let plaintext = match fs::read(fp) {
+        Ok(plaintext) => plaintext,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Encrypted::Missing),
+        Err(e) => return Err(e.to_string()),
+    };
+    let key = load_key()?;
+    let encrypted = encrypted_fp(fp);
+    let aad = associated_data(fp);
+    // an encrypted version which can't be decrypted, for example with an older key, is replaced
+    if let Ok(blob) = fs::read(&encrypted) {
+        if decrypt(&key, &blob, &aad).is_ok_and(|previous| previous == plaintext) {
+            return Ok(Encrypted::Unchanged);
+        }
+    }
+    fs::write(encrypted, encrypt(&key, &plaintext, &aad)).map_err(|e| e.to_string())?;
+    Ok(Encrypted::Written)
+}
+
+/// Write a file from its encrypted version, returning `false` if there is no such version
+///
+/// An existing file is only replaced when `force` is set.
+pub fn decrypt_file(fp: &str, force: bool) -> Result<bool, String> {
+    let blob = match fs::read(encrypted_fp(fp)) {
+        Ok(blob) => blob,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.to_string()),
+    };
+    if !force && Path::new(fp).exists() {
+        return Err(format!("{fp} already exists, use --force to replace it"));
+    }
+    let key = load_key()?;
+    fs::write(fp, decrypt(&key, &blob, &associated_data(fp))?).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn roundtrip() {
+        let key = parse_key(KEY).unwrap();
+        let blob = encrypt(&key, b"1000\n2000", b"day01.txt");
+        assert_eq!(decrypt(&key, &blob, b"day01.txt").unwrap(), b"1000\n2000");
+        // a new nonce is used each time
+        assert_ne!(blob, encrypt(&key, b"1000\n2000", b"day01.txt"));
+    }
+
+    #[test]
+    fn authenticated() {
+        let key = parse_key(KEY).unwrap();
+        let mut blob = encrypt(&key, b"1000\n2000", b"day01.txt");
+        let other_key = parse_key(&KEY.replace("00", "ff")).unwrap();
+        assert!(decrypt(&other_key, &blob, b"day01.txt").is_err());
+        // the file was renamed to another day's
+        assert!(decrypt(&key, &blob, b"day02.txt").is_err());
+        *blob.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&key, &blob, b"day01.txt").is_err());
+        assert!(decrypt(&key, &blob[..5], b"day01.txt").is_err());
+    }
+
+    #[test]
+    fn keys() {
+        assert!(parse_key(KEY).is_ok());
+        assert!(parse_key(&format!("{KEY}\n")).is_ok());
+        assert!(parse_key("0011").is_err());
+        assert!(parse_key("not hexadecimal").is_err());
+    }
+}
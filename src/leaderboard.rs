@@ -0,0 +1,192 @@
+//! Private leaderboard viewer, showing the stars of each member and when they were earned
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use chrono::Duration;
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::calendar;
+
+/// When a star was earned, as a UNIX timestamp
+#[derive(Debug, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Member {
+    pub name: Option<String>,
+    pub id: u64,
+    pub local_score: u64,
+    pub global_score: u64,
+    /// Stars by day then by part, both as strings ("1" to "25" and "1" or "2")
+    pub completion_day_level: BTreeMap<String, BTreeMap<String, Star>>,
+}
+
+impl Member {
+    /// Anonymous members don't have a name
+    fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    fn star(&self, day: u32, part: u32) -> Option<&Star> {
+        self.completion_day_level
+            .get(&day.to_string())?
+            .get(&part.to_string())
+    }
+}
+
+/// The JSON document returned by the leaderboard API
+#[derive(Debug, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub members: BTreeMap<String, Member>,
+}
+
+impl Leaderboard {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Members with the best local score first
+    fn ranked(&self) -> Vec<&Member> {
+        let mut members = self.members.values().collect::<Vec<_>>();
+        members.sort_by(|a, b| {
+            b.local_score
+                .cmp(&a.local_score)
+                .then_with(|| a.display_name().cmp(&b.display_name()))
+        });
+        members
+    }
+
+    /// Time elapsed between the puzzle's unlock (midnight US Eastern time) and the star
+    fn time_to_star(&self, day: u32, star: &Star) -> Duration {
+        let year = self.event.parse().unwrap_or(2022);
+        let unlock = calendar::unlock_time(year, day as usize);
+        Duration::seconds(star.get_star_ts - unlock.timestamp())
+    }
+
+    /// Render a table with a column per day and a row per member, then the time each star took
+    pub fn render(&self) -> String {
+        let members = self.ranked();
+        let width = members
+            .iter()
+            .map(|m| m.display_name().chars().count())
+            .max()
+            .unwrap_or_default();
+        let mut out = String::new();
+        // the day numbers are written vertically so the columns stay narrow
+        let tens = (1..=25)
+            .map(|d| char::from_digit(d / 10, 10).unwrap())
+            .collect::<String>();
+        let units = (1..=25)
+            .map(|d| char::from_digit(d % 10, 10).unwrap())
+            .collect::<String>();
+        let _ = writeln!(out, "{:width$}    {}", "", tens.replace('0', " "));
+        let _ = writeln!(out, "{:width$}    {units}  local global", "");
+        for (rank, member) in members.iter().enumerate() {
+            let stars = (1..=25)
+                .map(|day| match (member.star(day, 1), member.star(day, 2)) {
+                    (Some(_), Some(_)) => "*".yellow().to_string(),
+                    (Some(_), None) => "*".white().to_string(),
+                    _ => ".".dimmed().to_string(),
+                })
+                .collect::<String>();
+            let _ = writeln!(
+                out,
+                "{:<width$} {:>2} {stars} {:>6} {:>6}",
+                member.display_name(),
+                rank + 1,
+                member.local_score,
+                member.global_score
+            );
+        }
+        for member in members {
+            if member.completion_day_level.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "\n{}", member.display_name().bold());
+            for day in 1..=25 {
+                let times = (1..=2)
+                    .filter_map(|part| member.star(day, part))
+                    .map(|star| format_duration(self.time_to_star(day, star)))
+                    .collect::<Vec<_>>();
+                if !times.is_empty() {
+                    let _ = writeln!(out, "  day {day:>2}: {}", times.join(" / "));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Format a duration as hours, minutes and seconds, with the number of days when it's more than 24 hours
+fn format_duration(duration: Duration) -> String {
+    let days = duration.num_days();
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        duration.num_hours() % 24,
+        duration.num_minutes() % 60,
+        duration.num_seconds() % 60
+    );
+    if days > 0 {
+        format!("{days}d {time}")
+    } else {
+        time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"{
+        "owner_id": 1,
+        "event": "2022",
+        "members": {
+            "1": {
+                "id": 1, "name": "Alice", "stars": 3, "local_score": 5, "global_score": 0, "last_star_ts": 0,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1669870923, "star_index": 0},
+                        "2": {"get_star_ts": 1669871400, "star_index": 1}
+                    },
+                    "2": {"1": {"get_star_ts": 1670050800, "star_index": 2}}
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 1, "local_score": 2, "global_score": 0, "last_star_ts": 0,
+                "completion_day_level": {"1": {"1": {"get_star_ts": 1669953600, "star_index": 0}}}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn ranking() {
+        let leaderboard = Leaderboard::from_json(FIXTURE).unwrap();
+        let names = leaderboard
+            .ranked()
+            .iter()
+            .map(|m| m.display_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Alice", "(anonymous user #2)"]);
+    }
+
+    #[test]
+    fn star_times() {
+        let leaderboard = Leaderboard::from_json(FIXTURE).unwrap();
+        let alice = &leaderboard.members["1"];
+        let time = |day, part| {
+            format_duration(leaderboard.time_to_star(day, alice.star(day, part).unwrap()))
+        };
+        assert_eq!(time(1, 1), "00:02:03");
+        assert_eq!(time(1, 2), "00:10:00");
+        assert_eq!(time(2, 1), "1d 02:00:00");
+        let rendered = leaderboard.render();
+        assert!(rendered.contains("day  1: 00:02:03 / 00:10:00"));
+    }
+}
@@ -0,0 +1,80 @@
+//! Verbosity and colors of the output
+//!
+//! The answers and timings are printed on the standard output, while the solvers' debug output goes through `tracing`
+//! to the standard error, so that the two never get mixed up.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Colors when writing to a terminal, unless the `NO_COLOR` environment variable is set
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether only the answers should be printed, without the timings
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Whether an output stream should be colored
+fn use_colors(choice: ColorChoice, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+    }
+}
+
+/// The most detailed events shown for a verbosity, which is negative when quiet
+fn level(verbosity: i8) -> LevelFilter {
+    match verbosity {
+        i8::MIN..=-1 => LevelFilter::ERROR,
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Set up the colors and the `tracing` subscriber for the whole process
+///
+/// From `-v`, the spans of each day and part are shown with their timings when they close.
+pub fn init(verbosity: i8, color: ColorChoice) {
+    QUIET.store(verbosity < 0, Ordering::Relaxed);
+    colored::control::set_override(use_colors(color, io::stdout().is_terminal()));
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_ansi(use_colors(color, io::stderr().is_terminal()))
+        .with_max_level(level(verbosity))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_target(false)
+        // no timestamps, but the spans still show how long they took
+        .with_timer(())
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        assert_eq!(level(-1), LevelFilter::ERROR);
+        assert_eq!(level(0), LevelFilter::WARN);
+        assert_eq!(level(2), LevelFilter::DEBUG);
+        assert_eq!(level(5), LevelFilter::TRACE);
+        assert!(!use_colors(ColorChoice::Never, true));
+        assert!(use_colors(ColorChoice::Always, false));
+        assert!(!use_colors(ColorChoice::Auto, false));
+    }
+}
@@ -1,9 +1,38 @@
+mod answer;
+mod cache;
+mod calendar;
+mod compare;
+mod describe;
+mod determinism;
+// the days using each helper may be compiled out
+#[cfg_attr(not(feature = "all-days"), allow(dead_code))]
+mod geom;
+mod identify;
+mod inputs;
+mod leaderboard;
+mod logging;
 mod parser;
+mod profile;
+mod puzzle;
+mod repl;
+mod scaling;
+mod selection;
+mod serve;
+mod variants;
+mod watch;
+use cache::Cache;
 use chrono::prelude::*;
-use clap::{Parser, Subcommand};
+use clap::builder::RangedU64ValueParser;
+use clap::error::ErrorKind;
+use clap::ArgAction;
+use clap::{CommandFactory, Parser, Subcommand};
 use days::*;
+use rand::{rngs::StdRng, SeedableRng};
+use selection::Days;
 use std::fs;
-use std::time::Instant;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
 
 mod days;
 
@@ -15,136 +44,906 @@ const YEAR: usize = 2022;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Shows the spans of each day and part, then the solvers' debug output (-vv) and more (-vvv)"
+    )]
+    verbose: u8,
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Only prints the day headers, the answers and the errors"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = logging::ColorChoice::Auto,
+        help = "When to use colors, auto honoring NO_COLOR and only coloring terminals"
+    )]
+    color: logging::ColorChoice,
+    #[cfg(feature = "parallel")]
+    #[arg(
+        long,
+        global = true,
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Number of threads of the solvers which run in parallel, one per core by default"
+    )]
+    threads: Option<u16>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     Run {
-        #[arg(value_name = "DAY", help = "The number of the day you want to run")]
-        day: Option<String>,
-        #[arg(short, long, help = "Runs all days sequentially")]
+        #[arg(
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to run, like 5, 1-10 or 3,7,12"
+        )]
+        days: Option<Days>,
+        #[arg(
+            short,
+            long,
+            conflicts_with = "days",
+            help = "Runs all days sequentially"
+        )]
         all: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to run, all the other days are run if no days are given"
+        )]
+        exclude: Option<Days>,
+        #[arg(
+            short,
+            long,
+            value_parser = part_parser(),
+            help = "Runs only this part (1 or 2)"
+        )]
+        part: Option<usize>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Solves the parts which have an alternative solver with this name with it"
+        )]
+        variant: Option<String>,
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["all", "exclude", "part", "variant"],
+            help = "Runs the day again each time its input or answers file changes"
+        )]
+        watch: bool,
+        #[arg(
+            long,
+            help = "Computes the answers again instead of using the cached ones"
+        )]
+        no_cache: bool,
     },
     GetInput {
         #[arg(
-            value_name = "DAY",
-            help = "The number of the day you want to get the input for"
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to get the input for, like 5, 1-10 or 3,7,12"
+        )]
+        days: Option<Days>,
+        #[arg(
+            short,
+            long,
+            conflicts_with = "days",
+            help = "Downloads input for all days sequentially"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to download, all the other days are downloaded if no days are given"
+        )]
+        exclude: Option<Days>,
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["all", "exclude"],
+            help = "Waits for the puzzle to unlock (by default the next one), then downloads its input"
+        )]
+        wait: bool,
+    },
+    Check {
+        #[arg(
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to check, like 5, 1-10 or 3,7,12"
+        )]
+        days: Option<Days>,
+        #[arg(short, long, conflicts_with = "days", help = "Checks all days")]
+        all: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to check, all the other days are checked if no days are given"
         )]
-        day: Option<String>,
-        #[arg(short, long, help = "Downloads input for all days sequentially")]
+        exclude: Option<Days>,
+    },
+    Determinism {
+        #[arg(
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to check, like 5, 1-10 or 3,7,12"
+        )]
+        days: Option<Days>,
+        #[arg(short, long, conflicts_with = "days", help = "Checks all days")]
         all: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to check, all the other days are checked if no days are given"
+        )]
+        exclude: Option<Days>,
+        #[arg(
+            short,
+            long,
+            default_value_t = 3,
+            help = "Number of times each part is run with each number of threads"
+        )]
+        runs: usize,
+    },
+    Bench {
+        #[arg(
+            value_name = "DAY",
+            value_parser = selection::parse_compiled_day,
+            help = "The number of the day you want to benchmark"
+        )]
+        day: usize,
+        #[arg(
+            short,
+            long,
+            value_parser = part_parser(),
+            help = "Benchmarks only this part (1 or 2)"
+        )]
+        part: Option<usize>,
+        #[arg(
+            short,
+            long,
+            default_value_t = 10,
+            help = "Number of times each solver is run"
+        )]
+        iterations: usize,
+    },
+    Generate {
+        #[arg(
+            value_name = "DAY",
+            value_parser = selection::parse_compiled_day,
+            help = "The number of the day you want to generate an input for"
+        )]
+        day: usize,
+        #[arg(
+            long,
+            help = "Seed for the random generator, a random one is picked if not specified"
+        )]
+        seed: Option<u64>,
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = scale_parser(),
+            help = "Size of the input, 1 being about the size of a real puzzle input"
+        )]
+        scale: usize,
+    },
+    Differential {
+        #[arg(value_name = "DAY", value_parser = selection::parse_compiled_day, help = "The number of the day you want to check")]
+        day: usize,
+        #[arg(
+            long,
+            help = "Seed for the random generator, a random one is picked if not specified"
+        )]
+        seed: Option<u64>,
+        #[arg(
+            long,
+            default_value_t = 100,
+            help = "Number of random inputs to compare the solvers on"
+        )]
+        cases: usize,
+    },
+    Compare {
+        #[arg(
+            value_name = "DAY",
+            value_parser = selection::parse_compiled_day,
+            help = "The number of the day you want to compare"
+        )]
+        day: usize,
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "The program to compare with, run by the shell with the input on its standard input"
+        )]
+        with: String,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Input to compare on instead of the day's input, can be repeated"
+        )]
+        input: Vec<String>,
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 0,
+            help = "Number of random inputs to compare on as well"
+        )]
+        generated: usize,
+        #[arg(
+            long,
+            help = "Seed for the random generator, a random one is picked if not specified"
+        )]
+        seed: Option<u64>,
+        #[arg(
+            long,
+            default_value_t = 1,
+            value_parser = scale_parser(),
+            help = "Size of the random inputs, 1 being about the size of a real puzzle input"
+        )]
+        scale: usize,
+    },
+    Scale {
+        #[arg(value_name = "DAY", value_parser = selection::parse_compiled_day, help = "The number of the day you want to analyze")]
+        day: usize,
+        #[arg(
+            long,
+            help = "Seed for the random generator, a random one is picked if not specified"
+        )]
+        seed: Option<u64>,
+        #[arg(
+            long,
+            default_value_t = 8,
+            value_parser = scale_parser(),
+            help = "Largest input scale, scales are doubled from 1 until this one"
+        )]
+        max_scale: usize,
+    },
+    Profile {
+        #[arg(value_name = "DAY", value_parser = selection::parse_compiled_day, help = "The number of the day you want to profile")]
+        day: usize,
+        #[arg(
+            value_name = "PART",
+            value_parser = part_parser(),
+            help = "The part you want to profile (1 or 2)"
+        )]
+        part: usize,
+        #[arg(
+            short,
+            long,
+            default_value_t = 10,
+            help = "Number of times the part is run"
+        )]
+        iterations: usize,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write a Chrome tracing JSON file with the parse and solve spans"
+        )]
+        trace: Option<String>,
+    },
+    Inputs {
+        #[command(subcommand)]
+        action: InputsAction,
+    },
+    Serve {
+        #[arg(
+            long,
+            default_value_t = 3000,
+            help = "Port to listen on (on localhost only)"
+        )]
+        port: u16,
+        #[arg(
+            long,
+            default_value_t = 30,
+            help = "Number of seconds after which a request gives up waiting for the solvers"
+        )]
+        timeout: u64,
+        #[arg(
+            long,
+            value_parser = clap::value_parser!(u16).range(1..),
+            help = "Number of solvers which can run at once, one per core by default, more requests being refused"
+        )]
+        max_solvers: Option<u16>,
+    },
+    Repl {
+        #[arg(value_name = "DAY", value_parser = selection::parse_compiled_day, help = "The number of the day you want to explore")]
+        day: usize,
+    },
+    Leaderboard {
+        #[arg(
+            value_name = "ID",
+            required_unless_present = "file",
+            help = "The ID of the private leaderboard, found at the end of its URL"
+        )]
+        id: Option<String>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with = "id",
+            help = "Reads the leaderboard from a JSON file instead of downloading it"
+        )]
+        file: Option<String>,
+        #[arg(
+            long,
+            default_value = "https://adventofcode.com",
+            help = "Base URL of the Advent of Code website"
+        )]
+        base_url: String,
+    },
+    Describe {
+        #[arg(
+            value_name = "DAY",
+            value_parser = selection::parse_compiled_day,
+            help = "The number of the day whose input you want to describe"
+        )]
+        day: usize,
+    },
+    Identify {
+        #[arg(
+            value_name = "FILE",
+            help = "The input to identify, or - to read it from the standard input"
+        )]
+        file: String,
+    },
+    Read {
+        #[arg(value_name = "DAY", value_parser = selection::parse_day, help = "The number of the day you want to read")]
+        day: usize,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Reads the puzzle from an HTML file instead of the cached or downloaded page"
+        )]
+        file: Option<String>,
+        #[arg(
+            long,
+            help = "Downloads the page again, for example once part 2 is unlocked"
+        )]
+        refresh: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Saves the Nth code block of the puzzle as an example input, can be repeated"
+        )]
+        save: Vec<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum InputsAction {
+    Keygen,
+    Encrypt {
+        #[arg(
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to encrypt, like 5, 1-10 or 3,7,12, all of them by default"
+        )]
+        days: Option<Days>,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to encrypt"
+        )]
+        exclude: Option<Days>,
+    },
+    Decrypt {
+        #[arg(
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "The days you want to decrypt, like 5, 1-10 or 3,7,12, all of them by default"
+        )]
+        days: Option<Days>,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            value_parser = selection::parse_days,
+            help = "Days not to decrypt"
+        )]
+        exclude: Option<Days>,
+        #[arg(long, help = "Replaces the files which exist already")]
+        force: bool,
     },
 }
 
 fn main() {
     let cli = Cli::parse();
+    let verbosity = match cli.quiet {
+        true => -1,
+        false => cli.verbose.min(3) as i8,
+    };
+    logging::init(verbosity, cli.color);
+    #[cfg(feature = "parallel")]
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.into())
+            .build_global()
+            .expect("Could not create the thread pool");
+    }
 
     match &cli.command {
-        Commands::Run { day, all } => {
-            if *all {
-                run_all_days();
+        Commands::Run {
+            days,
+            all,
+            exclude,
+            part,
+            variant,
+            watch,
+            no_cache,
+        } => {
+            let mut cache = Cache::load(!*no_cache);
+            let days = select_days(days, *all, exclude, "run").compiled();
+            if *watch {
+                let &[day] = days.0.as_slice() else {
+                    Cli::command()
+                        .error(ErrorKind::ArgumentConflict, "Only one day can be watched")
+                        .exit()
+                };
+                let (input_fp, answers_fp) = (input_fp(day), answers_fp(day));
+                with_day!(day, D => watch::watch::<D>(&input_fp, &answers_fp));
             } else {
-                match day {
-                    Some(day) => run_day(parse_day(day)),
-                    None => {
-                        println!("No day parameter specified, attempting to run today");
-                        let now_day = get_today();
-                        println!("Running day {now_day}");
-                        run_day(now_day);
-                    }
+                for day in days.0 {
+                    run_day(day, *part, variant.as_deref(), &mut cache);
                 }
             }
+            cache.save();
         }
-        Commands::GetInput { day, all } => {
-            if *all {
-                download_all_input();
+        Commands::GetInput {
+            days,
+            all,
+            exclude,
+            wait,
+        } => {
+            if *wait {
+                let day = match days.as_ref().map(|days| days.0.as_slice()) {
+                    Some(&[day]) => day,
+                    Some(_) => Cli::command()
+                        .error(
+                            ErrorKind::ArgumentConflict,
+                            "Only one day can be waited for",
+                        )
+                        .exit(),
+                    None => calendar::next_unlock(Utc::now(), YEAR as i32).unwrap_or_else(|| {
+                        Cli::command()
+                            .error(
+                                ErrorKind::MissingRequiredArgument,
+                                "All the puzzles are unlocked already, please specify a day",
+                            )
+                            .exit()
+                    }),
+                };
+                wait_for_unlock(day);
+                download_input(day);
             } else {
-                match day {
-                    Some(day) => download_input(parse_day(day)),
-                    None => {
-                        println!(
-                            "No day parameter specified, attempting to download today's input"
-                        );
-                        let now_day = get_today();
-                        println!("Getting input for day {now_day}");
-                        download_input(now_day);
+                for day in select_days(days, *all, exclude, "download the input of").0 {
+                    download_input(day);
+                }
+            }
+        }
+        Commands::Check { days, all, exclude } => {
+            let mut agree = true;
+            for day in select_days(days, *all, exclude, "check").compiled().0 {
+                println!("======== DAY {day} ========");
+                let input_fp = &input_fp(day);
+                agree &= with_day!(day, D => variants::check::<D>(input_fp));
+            }
+            if !agree {
+                std::process::exit(1);
+            }
+        }
+        Commands::Determinism {
+            days,
+            all,
+            exclude,
+            runs,
+        } => {
+            // the global pool has the number of threads given with --threads
+            #[cfg(feature = "parallel")]
+            let threads = rayon::current_num_threads();
+            // without the parallel days, every solver runs on the main thread
+            #[cfg(not(feature = "parallel"))]
+            let threads = 1;
+            let mut deterministic = true;
+            for day in select_days(days, *all, exclude, "check").compiled().0 {
+                println!("======== DAY {day} ========");
+                let input_fp = &input_fp(day);
+                deterministic &=
+                    with_day!(day, D => determinism::check::<D>(input_fp, threads, *runs));
+            }
+            if !deterministic {
+                std::process::exit(1);
+            }
+        }
+        Commands::Bench {
+            day,
+            part,
+            iterations,
+        } => {
+            let parts = match part {
+                Some(part) => vec![*part],
+                None => vec![1, 2],
+            };
+            let input_fp = &input_fp(*day);
+            with_day!(*day, D => variants::bench::<D>(input_fp, &parts, *iterations));
+        }
+        Commands::Generate { day, seed, scale } => {
+            let seed = seed.unwrap_or_else(|| {
+                let seed = rand::random();
+                eprintln!("Using seed {seed}");
+                seed
+            });
+            generate_input(*day, seed, *scale);
+        }
+        Commands::Differential { day, seed, cases } => {
+            let seed = seed.unwrap_or_else(rand::random);
+            eprintln!("Using seed {seed}");
+            if !check_differential(*day, seed, *cases) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Compare {
+            day,
+            with,
+            input,
+            generated,
+            seed,
+            scale,
+        } => {
+            let seed = seed.unwrap_or_else(rand::random);
+            let (cases, read) = compare_cases(*day, input, *generated, seed, *scale);
+            // the cases which could be read are still compared
+            if !with_day!(*day, D => compare::compare::<D>(with, &cases)) || !read {
+                std::process::exit(1);
+            }
+        }
+        Commands::Scale {
+            day,
+            seed,
+            max_scale,
+        } => {
+            let seed = seed.unwrap_or_else(rand::random);
+            eprintln!("Using seed {seed}");
+            let scales = std::iter::successors(Some(1), |s| Some(s * 2))
+                .take_while(|s| s <= max_scale)
+                .collect::<Vec<_>>();
+            with_day!(*day, D => scaling::analyze::<D>(seed, &scales));
+        }
+        Commands::Profile {
+            day,
+            part,
+            iterations,
+            trace,
+        } => {
+            let input_fp = &input_fp(*day);
+            with_day!(*day, D => profile::profile::<D>(input_fp, *part, *iterations, trace.as_deref()));
+        }
+        Commands::Inputs { action } => match action {
+            InputsAction::Keygen => match inputs::generate_key() {
+                Ok(()) => {
+                    println!("Successfully created .inputs.key, keep it out of the repository")
+                }
+                Err(e) => eprintln!("Could not create the key: {e}"),
+            },
+            InputsAction::Encrypt { days, exclude } => {
+                for day in select_days(days, days.is_none(), exclude, "encrypt").0 {
+                    for fp in [input_fp(day), answers_fp(day)] {
+                        match inputs::encrypt_file(&fp) {
+                            Ok(inputs::Encrypted::Written) => println!("Encrypted {fp}"),
+                            Ok(inputs::Encrypted::Unchanged | inputs::Encrypted::Missing) => {}
+                            Err(e) => eprintln!("Could not encrypt {fp}: {e}"),
+                        }
+                    }
+                }
+            }
+            InputsAction::Decrypt {
+                days,
+                exclude,
+                force,
+            } => {
+                for day in select_days(days, days.is_none(), exclude, "decrypt").0 {
+                    for fp in [input_fp(day), answers_fp(day)] {
+                        match inputs::decrypt_file(&fp, *force) {
+                            Ok(true) => println!("Decrypted {fp}"),
+                            Ok(false) => {}
+                            Err(e) => eprintln!("Could not decrypt {fp}: {e}"),
+                        }
                     }
                 }
             }
+        },
+        Commands::Serve {
+            port,
+            timeout,
+            max_solvers,
+        } => {
+            let max_solvers = max_solvers
+                .map(usize::from)
+                .unwrap_or_else(|| thread::available_parallelism().map_or(1, |cores| cores.get()));
+            serve::serve(*port, Duration::from_secs(*timeout), max_solvers);
+        }
+        Commands::Repl { day } => {
+            let input_fp = &input_fp(*day);
+            with_day!(*day, D => repl::repl::<D>(input_fp));
+        }
+        Commands::Describe { day } => {
+            if let Err(e) = with_day!(*day, D => describe::describe::<D>(&input_fp(*day))) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
+        Commands::Identify { file } => {
+            let text = match file.as_str() {
+                "-" => io::read_to_string(io::stdin()).expect("Could not read the standard input"),
+                fp => inputs::read(fp).unwrap_or_else(|e| {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }),
+            };
+            if !identify::report(&text) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Read {
+            day,
+            file,
+            refresh,
+            save,
+        } => read_puzzle(*day, file.as_deref(), *refresh, save),
+        Commands::Leaderboard { id, file, base_url } => {
+            let json = match (id, file) {
+                (_, Some(file)) => {
+                    fs::read_to_string(file).expect("Could not read leaderboard file")
+                }
+                (Some(id), None) => download_leaderboard(base_url, id),
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            match leaderboard::Leaderboard::from_json(&json) {
+                Ok(leaderboard) => print!("{}", leaderboard.render()),
+                Err(e) => eprintln!("Could not read leaderboard: {e}"),
+            }
+        }
+    }
+}
+
+/// Parser for the part arguments, which only accepts 1 and 2
+fn part_parser() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::new().range(1..=2)
+}
+
+/// Parser for the input scales, which must be at least 1 for the generators to produce valid inputs
+fn scale_parser() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::new().range(1..)
+}
+
+/// The days chosen on the command line, without the excluded ones
+///
+/// With neither days nor `--all`, the excluded days are taken out of all the days if there are some, otherwise
+/// today's puzzle is chosen.
+fn select_days(days: &Option<Days>, all: bool, exclude: &Option<Days>, action: &str) -> Days {
+    let days = match (days, exclude) {
+        (Some(days), _) => days.clone(),
+        (None, exclude) if all || exclude.is_some() => Days::all(),
+        (None, _) => {
+            eprintln!("No day parameter specified, attempting to {action} today's puzzle");
+            let now_day = get_today();
+            eprintln!("Day {now_day} it is");
+            Days(vec![now_day])
         }
+    };
+    match exclude {
+        Some(exclude) => days.without(exclude),
+        None => days,
     }
 }
 
 fn get_today() -> usize {
-    let now = Local::now();
-    let now_day = now.day();
-    if now.month() == 12 && (1..=25).contains(&now_day) {
-        now_day.try_into().unwrap()
-    } else {
-        panic!("Today is not a valid Advent of Code day. Please specify a day");
+    calendar::puzzle_day(Utc::now()).unwrap_or_else(|| {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "Today is not a valid Advent of Code day, please specify a day",
+            )
+            .exit()
+    })
+}
+
+/// Count down until the puzzle of the given day unlocks
+fn wait_for_unlock(day: usize) {
+    let unlock = calendar::unlock_time(YEAR as i32, day);
+    loop {
+        let remaining = (unlock - Utc::now()).num_seconds();
+        if remaining < 0 {
+            break;
+        }
+        print!(
+            "\rDay {day} unlocks in {:02}:{:02}:{:02} ",
+            remaining / 3600,
+            remaining / 60 % 60,
+            remaining % 60
+        );
+        let _ = io::stdout().flush();
+        thread::sleep(Duration::from_millis(250));
     }
+    println!("\rDay {day} is unlocked, getting its input");
 }
 
-fn parse_day(day: &str) -> usize {
-    match day.parse() {
-        Ok(i) => {
-            if (i..=25).contains(&i) {
-                i
-            } else {
-                panic!("{i} is not a valid day. Only days 1-25 are allowed.")
+fn input_fp(day: usize) -> String {
+    format!("inputs/day{day:02}.txt")
+}
+
+/// The expected answers for a day, part 1 on the first line and part 2 on the following ones
+fn answers_fp(day: usize) -> String {
+    format!("inputs/day{day:02}.answers.txt")
+}
+
+/// The puzzle's page, cached next to the input
+fn puzzle_fp(day: usize) -> String {
+    format!("inputs/day{day:02}.html")
+}
+
+/// Example inputs from the puzzles, which the tests check can be parsed
+fn example_fp(day: usize, n: usize) -> String {
+    format!("inputs/examples/day{day:02}-{n}.txt")
+}
+
+// Panics if you provide a value outside the range of 1 to 25
+fn run_day(day: usize, part: Option<usize>, variant: Option<&str>, cache: &mut Cache) {
+    println!("======== DAY {day} ========");
+    let input_fp = &input_fp(day);
+    with_day!(day, D => D::run_day(day, input_fp, part, variant, cache))
+}
+
+/// Print a random input for the given day, the same seed and scale always giving the same input
+fn generate_input(day: usize, seed: u64, scale: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let input = with_day!(day, D => D::unparse(&D::generate(&mut rng, scale)));
+    // like the downloaded inputs, there is no trailing newline
+    print!("{input}");
+}
+
+/// Compare the solvers of a day with its reference solvers on many small random inputs, stopping at the first
+/// disagreement
+///
+/// Returns whether all the cases agree, which is not the case if the day has no reference solver.
+fn check_differential(day: usize, seed: u64, cases: usize) -> bool {
+    let mut rng = StdRng::seed_from_u64(seed);
+    with_day!(day, D => {
+        for case in 1..=cases {
+            let Some((input, disagreements)) = D::differential(&mut rng) else {
+                println!("Day {day} has no reference solver");
+                return false;
+            };
+            if !disagreements.is_empty() {
+                println!("Case {case} disagrees with the reference solver:");
+                for disagreement in disagreements {
+                    println!("  {disagreement}");
+                }
+                println!("Input:\n{}", D::unparse(&input));
+                return false;
             }
         }
-        Err(_) => {
-            panic!("{day} is not a valid day. Please provide a number.")
+        println!("All {cases} cases agree with the reference solver");
+        true
+    })
+}
+
+/// The inputs to compare a day on: the given files or else the day's input, then the generated ones
+///
+/// The k-th generated input uses the seed `seed + k`, so that it can be generated again on its own. The files which
+/// can't be read are reported and left out, the returned flag tells whether they all could be.
+fn compare_cases(
+    day: usize,
+    files: &[String],
+    generated: usize,
+    seed: u64,
+    scale: usize,
+) -> (Vec<compare::Case>, bool) {
+    let mut files = files.to_vec();
+    if files.is_empty() && generated == 0 {
+        files.push(input_fp(day));
+    }
+    let mut cases = Vec::with_capacity(files.len() + generated);
+    let mut read = true;
+    for fp in files {
+        match inputs::read(&fp) {
+            Ok(input) => cases.push(compare::Case { label: fp, input }),
+            Err(e) => {
+                read = false;
+                eprintln!("{fp}: {e}");
+            }
         }
     }
+    for k in 0..generated as u64 {
+        let seed = seed.wrapping_add(k);
+        let mut rng = StdRng::seed_from_u64(seed);
+        cases.push(compare::Case {
+            label: format!("generated with --seed {seed}"),
+            input: with_day!(day, D => D::unparse(&D::generate(&mut rng, scale))),
+        });
+    }
+    (cases, read)
 }
 
-fn run_all_days() {
-    (1..=25).map(run_day).collect()
+/// Print the puzzle's text and its code blocks, saving the chosen ones as example inputs
+fn read_puzzle(day: usize, file: Option<&str>, refresh: bool, save: &[usize]) {
+    let html = match file {
+        Some(file) => fs::read_to_string(file).expect("Could not read puzzle file"),
+        None => match fs::read_to_string(puzzle_fp(day)) {
+            Ok(html) if !refresh => html,
+            _ => download_puzzle(day),
+        },
+    };
+    let blocks = puzzle::code_blocks(&html);
+    // check every index up front so that nothing is printed or saved for a bad request
+    if let Some(n) = save.iter().find(|&&n| n == 0 || n > blocks.len()) {
+        eprintln!(
+            "There is no code block {n}, the puzzle has {}",
+            blocks.len()
+        );
+        std::process::exit(1);
+    }
+    println!("{}", puzzle::render(&html));
+    for (i, block) in blocks.iter().enumerate() {
+        let lines = block.lines().collect::<Vec<_>>();
+        println!(
+            "======== CODE BLOCK {} ({} lines) ========",
+            i + 1,
+            lines.len()
+        );
+        for line in lines.iter().take(5) {
+            println!("{line}");
+        }
+        if lines.len() > 5 {
+            println!("...");
+        }
+    }
+    for &n in save {
+        let block = &blocks[n - 1];
+        let path = example_fp(day, n);
+        fs::create_dir_all("inputs/examples").unwrap();
+        fs::write(&path, block).unwrap();
+        println!("Successfully saved code block {n} to {path}");
+    }
 }
-// Panics if you provide a value outside the range of 1 to 25
-fn run_day(day: usize) {
-    println!("======== DAY {day} ========");
-    // I'd like to do this with a macro, but I'm not sure how to do it.
-    let input_fp = &format!("inputs/day{day:02}.txt");
-    match day {
-        1 => day01::Day01::run_day(input_fp),
-        2 => day02::Day02::run_day(input_fp),
-        3 => day03::Day03::run_day(input_fp),
-        4 => day04::Day04::run_day(input_fp),
-        5 => day05::Day05::run_day(input_fp),
-        6 => day06::Day06::run_day(input_fp),
-        7 => day07::Day07::run_day(input_fp),
-        8 => day08::Day08::run_day(input_fp),
-        9 => day09::Day09::run_day(input_fp),
-        10 => day10::Day10::run_day(input_fp),
-        11 => day11::Day11::run_day(input_fp),
-        12 => day12::Day12::run_day(input_fp),
-        13 => day13::Day13::run_day(input_fp),
-        14 => day14::Day14::run_day(input_fp),
-        15 => day15::Day15::run_day(input_fp),
-        16 => day16::Day16::run_day(input_fp),
-        17 => day17::Day17::run_day(input_fp),
-        18 => day18::Day18::run_day(input_fp),
-        19 => day19::Day19::run_day(input_fp),
-        20 => day20::Day20::run_day(input_fp),
-        21 => day21::Day21::run_day(input_fp),
-        22 => day22::Day22::run_day(input_fp),
-        23 => day23::Day23::run_day(input_fp),
-        24 => day24::Day24::run_day(input_fp),
-        25 => day25::Day25::run_day(input_fp),
-        d => panic!("Provided unsupported day {d}"),
+
+fn download_puzzle(day: usize) -> String {
+    let session = read_session();
+    let url = format!("https://adventofcode.com/{YEAR}/day/{day}");
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .get(url)
+        .header("cookie", format!("session={session};"))
+        .send()
+        .unwrap();
+
+    if response.status().is_success() {
+        let html = response.text().unwrap();
+        fs::write(puzzle_fp(day), &html).unwrap();
+        html
+    } else {
+        panic!(
+            "Could not get the puzzle for day {day}. Is your correct session cookie in your .session file?"
+        )
     }
 }
 
-fn download_all_input() {
-    (1..=25).map(download_input).collect()
+/// Read session cookie from .session file
+fn read_session() -> String {
+    fs::read_to_string(".session").expect("Could not find .session file")
 }
 
 fn download_input(day: usize) {
-    // Read session cookie from .session file
-    let session = fs::read_to_string(".session").expect("Could not find .session file");
+    let session = read_session();
     let url = format!("https://adventofcode.com/{YEAR}/day/{day}/input");
     let client = reqwest::blocking::Client::new();
     let response = client
@@ -157,7 +956,7 @@ fn download_input(day: usize) {
         let mut text = response.text().unwrap();
         // Remove trailing newline
         text.pop();
-        let path = format!("inputs/day{day:02}.txt");
+        let path = input_fp(day);
         fs::write(&path, text).unwrap();
         println!("Successfully downloaded input to {}", &path);
     } else {
@@ -166,3 +965,22 @@ fn download_input(day: usize) {
         )
     }
 }
+
+fn download_leaderboard(base_url: &str, id: &str) -> String {
+    let session = read_session();
+    let url = format!("{base_url}/{YEAR}/leaderboard/private/view/{id}.json");
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .get(url)
+        .header("cookie", format!("session={session};"))
+        .send()
+        .unwrap();
+
+    if response.status().is_success() {
+        response.text().unwrap()
+    } else {
+        panic!(
+            "Could not get leaderboard {id}. Is your correct session cookie in your .session file, and are you a member?"
+        )
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use nom::error::Error;
 use nom::Err;
 
@@ -5,6 +7,23 @@ use nom::Err;
 pub enum MyErr {
     FileError(std::io::Error),
     ParseError(Err<Error<String>>),
+    Decryption(String),
+    #[cfg_attr(not(feature = "embed-inputs"), allow(dead_code))]
+    NotEmbedded(String),
+}
+
+impl Display for MyErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MyErr::FileError(e) => write!(f, "Could not read input file: {e}"),
+            MyErr::ParseError(e) => write!(f, "Could not parse input: {e}"),
+            MyErr::Decryption(e) => write!(f, "Could not decrypt input file: {e}"),
+            MyErr::NotEmbedded(fp) => write!(
+                f,
+                "Could not find input file {fp}, and it was not embedded in this binary"
+            ),
+        }
+    }
 }
 
 impl From<Err<Error<&str>>> for MyErr {
@@ -0,0 +1,110 @@
+//! Run a single part of a day many times, so that profilers spend their time in the solver rather than in the parsing
+//! or the process startup
+
+use std::fmt::Write as _;
+use std::fs;
+use std::hint::black_box;
+use std::time::Instant;
+
+use crate::days::Day;
+
+/// A span of time, in microseconds since the start of the profiling, as found in a trace
+struct Span {
+    name: String,
+    start: f64,
+    duration: f64,
+}
+
+/// Write the spans as a Chrome tracing JSON document, which can be opened in `chrome://tracing` or Perfetto
+fn trace_json(spans: &[Span]) -> String {
+    let mut json = String::from("{\"traceEvents\":[");
+    for (i, span) in spans.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        // complete events ("X") have both a start and a duration, the names never need escaping
+        let _ = write!(
+            json,
+            "{{\"name\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":1}}",
+            span.name, span.start, span.duration
+        );
+    }
+    json.push_str("]}");
+    json
+}
+
+/// Parse the input once, then run the given part `iterations` times, optionally writing a trace of the spans to
+/// `trace_fp`
+pub fn profile<D: Day>(fp: &str, part: usize, iterations: usize, trace_fp: Option<&str>) {
+    let profile_start = Instant::now();
+    let mut spans = Vec::with_capacity(iterations + 1);
+    let mut record = |name: String, start: Instant| {
+        spans.push(Span {
+            name,
+            start: start.duration_since(profile_start).as_nanos() as f64 / 1e3,
+            duration: start.elapsed().as_nanos() as f64 / 1e3,
+        })
+    };
+
+    let parse_start = Instant::now();
+    let input = match D::parse_file(fp) {
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+        Ok(input) => input,
+    };
+    record("parse".to_string(), parse_start);
+
+    let solve_start = Instant::now();
+    for i in 0..iterations {
+        let start = Instant::now();
+        match part {
+            1 => {
+                black_box(D::part_1(black_box(&input)));
+            }
+            2 => {
+                black_box(D::part_2(black_box(&input)));
+            }
+            p => panic!("{p} is not a valid part. Only parts 1 and 2 are allowed."),
+        }
+        record(format!("part {part} #{}", i + 1), start);
+    }
+    let elapsed = solve_start.elapsed().as_nanos() as f32 / 1e6;
+    println!(
+        "Part {part} ran {iterations} times in {elapsed}ms ({}ms per run)",
+        elapsed / iterations.max(1) as f32
+    );
+
+    if let Some(trace_fp) = trace_fp {
+        fs::write(trace_fp, trace_json(&spans)).unwrap();
+        println!("Successfully wrote trace to {trace_fp}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace() {
+        let spans = [
+            Span {
+                name: "parse".to_string(),
+                start: 0.,
+                duration: 1.5,
+            },
+            Span {
+                name: "part 1 #1".to_string(),
+                start: 2.,
+                duration: 10.25,
+            },
+        ];
+        assert_eq!(
+            trace_json(&spans),
+            "{\"traceEvents\":[\
+            {\"name\":\"parse\",\"ph\":\"X\",\"ts\":0.000,\"dur\":1.500,\"pid\":1,\"tid\":1},\
+            {\"name\":\"part 1 #1\",\"ph\":\"X\",\"ts\":2.000,\"dur\":10.250,\"pid\":1,\"tid\":1}]}"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Puzzle statements: render the text of a puzzle page in the terminal, and find the example inputs it contains
+
+/// Width of the rendered text
+const WIDTH: usize = 100;
+
+/// The parts of the page with the puzzle's text, one per part that was unlocked
+fn articles(html: &str) -> Vec<&str> {
+    let mut articles = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("<article") {
+        let Some(len) = rest[start..].find("</article>") else {
+            break;
+        };
+        let end = start + len + "</article>".len();
+        articles.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    articles
+}
+
+/// Replace the HTML entities which can appear in the puzzle's code blocks
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Remove the tags inside of a code block, which are used for emphasis
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Render the puzzle's text for the terminal
+pub fn render(html: &str) -> String {
+    articles(html)
+        .into_iter()
+        .map(|article| html2text::from_read(article.as_bytes(), WIDTH))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The content of the `<pre><code>` blocks of the puzzle's text, which are candidate example inputs
+///
+/// Like the downloaded inputs, they have no trailing newline.
+pub fn code_blocks(html: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    for article in articles(html) {
+        let mut rest = article;
+        while let Some(start) = rest.find("<pre><code>") {
+            let code = &rest[start + "<pre><code>".len()..];
+            let Some(end) = code.find("</code></pre>") else {
+                break;
+            };
+            let block = unescape(&strip_tags(&code[..end]));
+            blocks.push(block.strip_suffix('\n').unwrap_or(&block).to_string());
+            rest = &code[end..];
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<html><body><main>\
+        <article class=\"day-desc\"><h2>--- Day 1: Test ---</h2><p>Some text.</p>\
+        <pre><code>1000\n<em>2000</em>\n\n3000\n</code></pre></article>\
+        <p>Your puzzle answer was <code>42</code>.</p>\
+        <article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>\
+        <pre><code>a -&gt; b &amp;&amp; c\n</code></pre></article>\
+        </main></body></html>";
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            code_blocks(PAGE),
+            ["1000\n2000\n\n3000".to_string(), "a -> b && c".to_string()]
+        );
+    }
+
+    #[test]
+    fn text() {
+        let text = render(PAGE);
+        assert!(text.contains("Day 1: Test"));
+        assert!(text.contains("Part Two"));
+        // the answers are outside of the articles
+        assert!(!text.contains("42"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Interactive mode keeping a day's input loaded, to run the parts again or explore the input without re-parsing it
+
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::time::Instant;
+
+use crate::days::{self, Day};
+use crate::describe;
+use crate::inputs;
+use crate::parser::MyErr;
+
+/// Commands available for every day, as (usage, description) pairs
+const COMMANDS: [(&str, &str); 6] = [
+    ("1", "run part 1"),
+    ("2", "run part 2"),
+    ("stats", "print statistics about the input"),
+    ("reload", "read and parse the input file again"),
+    ("help", "list the commands"),
+    ("quit", "exit the REPL"),
+];
+
+/// Parse the argument at index `i` of a REPL command
+#[cfg_attr(
+    not(any(
+        feature = "day12",
+        feature = "day15",
+        feature = "day16",
+        feature = "day17"
+    )),
+    allow(dead_code)
+)]
+pub fn arg<T: FromStr>(args: &[&str], i: usize, name: &str) -> Result<T, String> {
+    let value = args.get(i).ok_or(format!("Missing argument {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {value} for {name}"))
+}
+
+/// The input file, as text and parsed
+struct Loaded<D: Day> {
+    text: String,
+    input: D::Input,
+}
+
+fn load<D: Day>(fp: &str) -> Result<Loaded<D>, MyErr> {
+    let text = inputs::read(fp)?;
+    let start = Instant::now();
+    let (_, input) = D::parse(&days::normalize(&text))?;
+    println!(
+        "Parsed {fp} in {}ms",
+        start.elapsed().as_nanos() as f32 / 1e6
+    );
+    Ok(Loaded { text, input })
+}
+
+fn print_help<D: Day>() {
+    for (usage, description) in COMMANDS.iter().chain(D::REPL_COMMANDS) {
+        println!("  {usage:<20} {description}");
+    }
+}
+
+/// Run a command, returning `false` when the REPL should exit
+fn run_command<D: Day>(fp: &str, loaded: &mut Loaded<D>, line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return true;
+    };
+    let args = words.collect::<Vec<_>>();
+    let start = Instant::now();
+    match command {
+        "1" => println!("{}", D::part_1(&loaded.input).into().labelled("Part 1")),
+        "2" => println!("{}", D::part_2(&loaded.input).into().labelled("Part 2")),
+        "stats" => {
+            describe::print::<D>(&loaded.text, &loaded.input);
+            return true;
+        }
+        "reload" => {
+            match load::<D>(fp) {
+                Ok(reloaded) => *loaded = reloaded,
+                Err(e) => println!("{e}"),
+            }
+            return true;
+        }
+        "help" => {
+            print_help::<D>();
+            return true;
+        }
+        "quit" | "exit" => return false,
+        _ => {
+            if let Err(e) = D::repl_command(&loaded.input, command, &args) {
+                println!("{e}, type `help` to list the commands");
+                return true;
+            }
+        }
+    }
+    println!("Took {}ms", start.elapsed().as_nanos() as f32 / 1e6);
+    true
+}
+
+/// Parse the input file once, then read commands from the standard input until it's closed
+pub fn repl<D: Day>(fp: &str) {
+    let mut loaded = match load::<D>(fp) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+    println!("Type `help` to list the commands");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        if !run_command(fp, &mut loaded, &line) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args() {
+        let args = ["12", "x"];
+        assert_eq!(arg::<usize>(&args, 0, "row"), Ok(12));
+        assert!(arg::<usize>(&args, 1, "column").is_err());
+        assert!(arg::<usize>(&args, 2, "other").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Empirical scaling analysis: time the solvers on generated inputs of increasing size and find out how their running
+//! time grows with the size of the input
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::days::Day;
+
+/// Width of the longest bar in the terminal chart
+const CHART_WIDTH: usize = 50;
+
+/// Expected running time for an input size, up to a constant factor
+type Growth = fn(f64) -> f64;
+
+/// Complexity classes the timings are compared to
+const CLASSES: [(&str, Growth); 5] = [
+    ("O(1)", |_| 1.),
+    ("O(n)", |n| n),
+    ("O(n log n)", |n| n * n.ln()),
+    ("O(n²)", |n| n * n),
+    ("O(n³)", |n| n * n * n),
+];
+
+/// Timing of both parts for an input of a given size (in bytes)
+struct Sample {
+    scale: usize,
+    size: usize,
+    part_1: Duration,
+    part_2: Duration,
+}
+
+/// Run a solver, keeping the best of up to 3 runs (slow solvers only run once)
+fn time_solver(solver: impl Fn()) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..3 {
+        let start = Instant::now();
+        solver();
+        best = best.min(start.elapsed());
+        if best > Duration::from_secs(1) {
+            break;
+        }
+    }
+    best
+}
+
+/// Duration in seconds, never zero so that it can be used on a log scale
+fn seconds(time: &Duration) -> f64 {
+    time.as_secs_f64().max(1e-9)
+}
+
+/// Slope of the least squares line going through the points
+fn slope(points: &[(f64, f64)]) -> f64 {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let covariance = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+    covariance / variance
+}
+
+/// Estimate `k` such that the time grows like `n^k`, by fitting a line on the log-log plot of time against size
+fn exponent(timings: &[(usize, Duration)]) -> f64 {
+    let points = timings
+        .iter()
+        .map(|(size, time)| ((*size as f64).ln(), seconds(time).ln()))
+        .collect::<Vec<_>>();
+    slope(&points)
+}
+
+/// Find the complexity class which best explains the timings
+///
+/// For each class, the ratio between the time and the expected time should be constant, so we pick the class for which
+/// that ratio varies the least (on a log scale, so that the largest inputs don't drown the others).
+fn best_class(timings: &[(usize, Duration)]) -> &'static str {
+    CLASSES
+        .iter()
+        .map(|(name, expected)| {
+            let ratios = timings
+                .iter()
+                .map(|(size, time)| seconds(time).ln() - expected(*size as f64).ln())
+                .collect::<Vec<_>>();
+            let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+            let variance = ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>();
+            (name, variance)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, _)| *name)
+        .unwrap()
+}
+
+/// Print a bar chart of the timings, along with the estimated complexity
+fn print_report(part: usize, samples: &[Sample], time: impl Fn(&Sample) -> Duration) {
+    let timings = samples
+        .iter()
+        .map(|s| (s.size, time(s)))
+        .collect::<Vec<_>>();
+    let longest = timings.iter().map(|(_, t)| *t).max().unwrap();
+    println!("Part {part}:");
+    for (sample, (size, time)) in samples.iter().zip(&timings) {
+        let width = (seconds(time) / seconds(&longest) * CHART_WIDTH as f64).round();
+        println!(
+            "  scale {:>3} ({size:>9} bytes) {} {}ms",
+            sample.scale,
+            "█".repeat(width as usize).cyan(),
+            time.as_nanos() as f32 / 1e6
+        );
+    }
+    println!(
+        "  time grows like n^{:.2}, closest to {}",
+        exponent(&timings),
+        best_class(&timings).bold()
+    );
+}
+
+/// Time both parts of a day on generated inputs for each scale, then report how the running time grows
+pub fn analyze<D: Day>(seed: u64, scales: &[usize]) {
+    let mut samples = Vec::with_capacity(scales.len());
+    for &scale in scales {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let input = D::generate(&mut rng, scale);
+        let size = D::unparse(&input).len();
+        let part_1 = time_solver(|| {
+            black_box(D::part_1(&input));
+        });
+        let part_2 = time_solver(|| {
+            black_box(D::part_2(&input));
+        });
+        println!(
+            "Scale {scale}: part 1 took {}ms, part 2 took {}ms",
+            part_1.as_nanos() as f32 / 1e6,
+            part_2.as_nanos() as f32 / 1e6
+        );
+        samples.push(Sample {
+            scale,
+            size,
+            part_1,
+            part_2,
+        });
+    }
+    if samples.iter().map(|s| s.size).min() == samples.iter().map(|s| s.size).max() {
+        println!("The generated inputs all have the same size, the complexity cannot be estimated");
+        return;
+    }
+    print_report(1, &samples, |s| s.part_1);
+    print_report(2, &samples, |s| s.part_2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(f: impl Fn(f64) -> f64) -> Vec<(usize, Duration)> {
+        [1_000, 2_000, 4_000, 8_000, 16_000]
+            .into_iter()
+            .map(|n| (n, Duration::from_secs_f64(f(n as f64) * 1e-9)))
+            .collect()
+    }
+
+    #[test]
+    fn exponents() {
+        assert!((exponent(&timings(|n| 3. * n)) - 1.).abs() < 1e-6);
+        assert!((exponent(&timings(|n| n * n / 2.)) - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(best_class(&timings(|_| 5_000.)), "O(1)");
+        assert_eq!(best_class(&timings(|n| 7. * n)), "O(n)");
+        assert_eq!(best_class(&timings(|n| n * n.ln())), "O(n log n)");
+        assert_eq!(best_class(&timings(|n| 0.1 * n * n)), "O(n²)");
+        assert_eq!(best_class(&timings(|n| n * n * n)), "O(n³)");
+    }
+}
@@ -0,0 +1,122 @@
+//! Parsing of the days given on the command line
+
+use std::collections::BTreeSet;
+
+use crate::days;
+
+/// Parse a single day, from 1 to 25
+pub fn parse_day(day: &str) -> Result<usize, String> {
+    let day = day
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| format!("{day} is not a valid day, please provide a number"))?;
+    if (1..=25).contains(&day) {
+        Ok(day)
+    } else {
+        Err(format!(
+            "{day} is not a valid day, only days 1-25 are allowed"
+        ))
+    }
+}
+
+/// Parse a single day, which must have been compiled in
+pub fn parse_compiled_day(day: &str) -> Result<usize, String> {
+    let day = parse_day(day)?;
+    if days::compiled(day) {
+        Ok(day)
+    } else {
+        Err(format!(
+            "day {day} was compiled out, build with `--features day{day:02}` to include it"
+        ))
+    }
+}
+
+/// A set of days, sorted and without duplicates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Days(pub Vec<usize>);
+
+impl Days {
+    pub fn all() -> Self {
+        Self((1..=25).collect())
+    }
+
+    pub fn without(self, excluded: &Days) -> Self {
+        Self(
+            self.0
+                .into_iter()
+                .filter(|d| !excluded.0.contains(d))
+                .collect(),
+        )
+    }
+
+    /// The days which were compiled in, telling about the other ones
+    pub fn compiled(self) -> Self {
+        let (compiled, compiled_out): (Vec<_>, Vec<_>) =
+            self.0.into_iter().partition(|&day| days::compiled(day));
+        match compiled_out[..] {
+            [] => {}
+            [day] => eprintln!(
+                "Skipping day {day}, which was compiled out (build with `--features day{day:02}` to include it)"
+            ),
+            _ => eprintln!(
+                "Skipping days {compiled_out:?}, which were compiled out (build with `--features all-days` to include them)"
+            ),
+        }
+        Self(compiled)
+    }
+}
+
+/// Parse a selection of days: single days (`5`), inclusive ranges (`1-10`) or a comma-separated list of those
+/// (`3,7,12-14`)
+pub fn parse_days(days: &str) -> Result<Days, String> {
+    let mut selection = BTreeSet::new();
+    for item in days.split(',') {
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse_day(start)?, parse_day(end)?);
+                if start > end {
+                    return Err(format!(
+                        "{item} is not a valid range, {start} is after {end}"
+                    ));
+                }
+                selection.extend(start..=end);
+            }
+            None => {
+                selection.insert(parse_day(item)?);
+            }
+        }
+    }
+    Ok(Days(selection.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single() {
+        assert_eq!(parse_day("5"), Ok(5));
+        assert_eq!(parse_day("25"), Ok(25));
+        assert!(parse_day("0").is_err());
+        assert!(parse_day("26").is_err());
+        assert!(parse_day("five").is_err());
+    }
+
+    #[test]
+    fn selections() {
+        let days = |s| parse_days(s).map(|days| days.0);
+        assert_eq!(days("5"), Ok(vec![5]));
+        assert_eq!(days("1-4"), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(days("12,3,7"), Ok(vec![3, 7, 12]));
+        assert_eq!(days("3-5,4,24-25"), Ok(vec![3, 4, 5, 24, 25]));
+        assert!(parse_days("5-3").is_err());
+        assert!(parse_days("0-3").is_err());
+        assert!(parse_days("1,").is_err());
+    }
+
+    #[test]
+    fn exclusion() {
+        let days = Days::all().without(&parse_days("2-24").unwrap());
+        assert_eq!(days, Days(vec![1, 25]));
+    }
+}
@@ -0,0 +1,263 @@
+//! Local HTTP service giving access to the solvers to other tools
+//!
+//! `POST /solve/{day}` with the raw puzzle input as the body returns the answers and timings as JSON.
+
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::answer::Answer;
+use crate::days::{self, with_day, Day};
+use crate::parser::MyErr;
+
+/// Largest input accepted, far above the size of any puzzle input
+const MAX_BODY_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Number of solver threads currently running, including the ones whose request timed out
+///
+/// A solver can't be interrupted, so limiting how many run at once is what keeps slow inputs from using every core.
+#[derive(Clone)]
+struct Solvers {
+    running: Arc<AtomicUsize>,
+    max: usize,
+}
+
+/// A running solver, which frees its place when dropped at the end of its thread, even if it panicked
+struct Slot(Arc<AtomicUsize>);
+
+impl Solvers {
+    fn new(max: usize) -> Self {
+        Solvers {
+            running: Arc::new(AtomicUsize::new(0)),
+            max,
+        }
+    }
+
+    /// Take a place for a new solver, unless the maximum number of them are already running
+    fn acquire(&self) -> Option<Slot> {
+        self.running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < self.max).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| Slot(Arc::clone(&self.running)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Progress of a solver thread
+enum Event {
+    Parsed(Result<(), String>, Duration),
+    Solved(usize, Answer, Duration),
+}
+
+/// Parse the input and solve both parts, reporting each step as soon as it's done
+fn solve<D: Day>(input: &str, events: &Sender<Event>) {
+    let start = Instant::now();
+    let input = days::normalize(input);
+    let input = match D::parse(&input) {
+        // the whole input should be understood
+        Ok(("", input)) => input,
+        result => {
+            let error = match result {
+                Ok((rest, _)) => format!(
+                    "Could not parse input from: {}",
+                    rest.lines().next().unwrap_or_default()
+                ),
+                Err(e) => MyErr::from(e).to_string(),
+            };
+            let _ = events.send(Event::Parsed(Err(error), start.elapsed()));
+            return;
+        }
+    };
+    let _ = events.send(Event::Parsed(Ok(()), start.elapsed()));
+    let start = Instant::now();
+    let answer = D::part_1(&input).into();
+    let _ = events.send(Event::Solved(1, answer, start.elapsed()));
+    let start = Instant::now();
+    let answer = D::part_2(&input).into();
+    let _ = events.send(Event::Solved(2, answer, start.elapsed()));
+}
+
+fn millis(time: Duration) -> f32 {
+    time.as_nanos() as f32 / 1e6
+}
+
+/// Gather the events of a solver thread until it's done or the deadline has passed
+///
+/// Returns the HTTP status code and the JSON body. Steps that were not done in time are `null`.
+fn collect(day: usize, events: Receiver<Event>, timeout: Duration) -> (u16, Value) {
+    let deadline = Instant::now() + timeout;
+    let mut body = json!({ "day": day, "parse": null, "part_1": null, "part_2": null });
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match events.recv_timeout(remaining) {
+            Ok(Event::Parsed(Ok(()), time)) => {
+                body["parse"] = json!({ "time_ms": millis(time) });
+            }
+            Ok(Event::Parsed(Err(error), time)) => {
+                body["parse"] = json!({ "time_ms": millis(time), "error": error });
+                return (400, body);
+            }
+            Ok(Event::Solved(part, answer, time)) => {
+                body[format!("part_{part}")] =
+                    json!({ "answer": answer.to_json(), "time_ms": millis(time) });
+                if part == 2 {
+                    return (200, body);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                body["error"] = json!(format!("timed out after {}s", timeout.as_secs_f32()));
+                return (504, body);
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                // the thread stopped without sending all the events, which only happens when it panics
+                body["error"] = json!("the solver panicked");
+                return (500, body);
+            }
+        }
+    }
+}
+
+/// Read the whole body of a request, unless it's larger than `MAX_BODY_BYTES`
+fn read_body(request: &mut Request) -> Result<String, (u16, Value)> {
+    let too_large = || {
+        (
+            413,
+            json!({ "error": format!("the input is larger than {MAX_BODY_BYTES} bytes") }),
+        )
+    };
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > MAX_BODY_BYTES)
+    {
+        return Err(too_large());
+    }
+    let mut input = String::new();
+    // reading one more byte than allowed tells if the body was too large without reading all of it
+    match request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut input)
+    {
+        Ok(length) if length as u64 > MAX_BODY_BYTES => Err(too_large()),
+        Ok(_) => Ok(input),
+        Err(e) => Err((
+            400,
+            json!({ "error": format!("Could not read input: {e}") }),
+        )),
+    }
+}
+
+/// Route a request and send the response
+fn handle(mut request: Request, timeout: Duration, solvers: &Solvers) {
+    let day = request
+        .url()
+        .strip_prefix("/solve/")
+        .and_then(|day| day.parse::<usize>().ok())
+        .filter(|&day| days::compiled(day));
+    let (status, body) = match (request.method(), day) {
+        (Method::Post, Some(day)) => match read_body(&mut request) {
+            Ok(input) => match solvers.acquire() {
+                Some(slot) => {
+                    let (sender, receiver) = mpsc::channel();
+                    // a solver can't be interrupted, so after a timeout its thread keeps running (and keeps its slot)
+                    // until it's done, but nobody listens to it anymore
+                    thread::spawn(move || {
+                        let _slot = slot;
+                        with_day!(day, D => solve::<D>(&input, &sender))
+                    });
+                    collect(day, receiver, timeout)
+                }
+                None => (
+                    503,
+                    json!({ "error": format!(
+                        "{} solvers are already running, try again later",
+                        solvers.max
+                    ) }),
+                ),
+            },
+            Err(response) => response,
+        },
+        (_, Some(_)) => (405, json!({ "error": "only POST is allowed" })),
+        (_, None) => (
+            404,
+            json!({ "error": format!(
+                "expected POST /solve/{{day}} with a day from 1 to 25, except {:?} which were compiled out",
+                days::compiled_out()
+            ) }),
+        ),
+    };
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+    let _ = request.respond(response);
+}
+
+/// Listen on the given port and answer each request in its own thread, with at most `max_solvers` solvers running
+pub fn serve(port: u16, timeout: Duration, max_solvers: usize) {
+    let server = Server::http(("127.0.0.1", port)).expect("Could not start the server");
+    println!("Listening on http://127.0.0.1:{port}/solve/{{day}}");
+    let solvers = Solvers::new(max_solvers);
+    for request in server.incoming_requests() {
+        let solvers = solvers.clone();
+        thread::spawn(move || handle(request, timeout, &solvers));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "day06")]
+    fn solved() {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            solve::<crate::days::day06::Day06>("mjqjpqmgbljsphdztnvjfqwrcgsmlb", &sender)
+        });
+        let (status, body) = collect(6, receiver, Duration::from_secs(10));
+        assert_eq!(status, 200);
+        assert_eq!(body["part_1"]["answer"], 7);
+        assert_eq!(body["part_2"]["answer"], 19);
+    }
+
+    #[test]
+    #[cfg(feature = "day04")]
+    fn parse_error() {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || solve::<crate::days::day04::Day04>("2-4,6-8\ngarbage", &sender));
+        let (status, body) = collect(4, receiver, Duration::from_secs(10));
+        assert_eq!(status, 400);
+        assert!(body["parse"]["error"].is_string());
+    }
+
+    #[test]
+    fn solvers_limit() {
+        let solvers = Solvers::new(2);
+        let first = solvers.acquire().unwrap();
+        let _second = solvers.acquire().unwrap();
+        assert!(solvers.acquire().is_none());
+        drop(first);
+        assert!(solvers.acquire().is_some());
+    }
+
+    #[test]
+    fn timed_out() {
+        let (_sender, receiver) = mpsc::channel();
+        let (status, body) = collect(16, receiver, Duration::from_millis(10));
+        assert_eq!(status, 504);
+        assert_eq!(body["part_1"], Value::Null);
+    }
+}
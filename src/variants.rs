@@ -0,0 +1,91 @@
+//! Alternative solvers of the days: check that they agree with the main ones, and compare how fast they are
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::answer::Answer;
+use crate::days::Day;
+
+/// The solvers of a part which don't give the same answer as its main solver, along with their answers
+///
+/// Also returns the answer of the main solver.
+pub fn disagreements<D: Day>(
+    input: &D::Input,
+    part: usize,
+) -> (Answer, Vec<(&'static str, Answer)>) {
+    let mut names = D::variant_names(part).into_iter();
+    let expected = D::solve(input, part, names.next().unwrap()).unwrap();
+    let disagreements = names
+        .map(|name| (name, D::solve(input, part, name).unwrap()))
+        .filter(|(_, answer)| *answer != expected)
+        .collect();
+    (expected, disagreements)
+}
+
+/// Run all the solvers of both parts on the input, returning whether they all agree
+///
+/// An input which can't be read or parsed fails the check, as nothing could be compared.
+pub fn check<D: Day>(fp: &str) -> bool {
+    let input = match D::parse_file(fp) {
+        Err(e) => {
+            eprintln!("{e}");
+            return false;
+        }
+        Ok(input) => input,
+    };
+    let mut agree = true;
+    for part in 1..=2 {
+        let names = D::variant_names(part);
+        let (expected, disagreements) = disagreements::<D>(&input, part);
+        if disagreements.is_empty() {
+            println!(
+                "Part {part}: {} {} ({})",
+                "ok".green(),
+                expected,
+                names.join(", ")
+            );
+        } else {
+            agree = false;
+            println!("Part {part}: {} default gives {expected}", "mismatch".red());
+            for (name, answer) in disagreements {
+                println!("  {name} gives {answer}");
+            }
+        }
+    }
+    agree
+}
+
+/// Time each solver of the given parts `iterations` times, and print them side by side
+pub fn bench<D: Day>(fp: &str, parts: &[usize], iterations: usize) {
+    let input = match D::parse_file(fp) {
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+        Ok(input) => input,
+    };
+    for &part in parts {
+        let names = D::variant_names(part);
+        let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+        println!("Part {part}:");
+        let mut default_ms = None;
+        for name in names {
+            let mut times = Vec::with_capacity(iterations);
+            for _ in 0..iterations {
+                let start = Instant::now();
+                black_box(D::solve(black_box(&input), part, name));
+                times.push(start.elapsed().as_nanos() as f32 / 1e6);
+            }
+            let mean = times.iter().sum::<f32>() / iterations.max(1) as f32;
+            let min = times.iter().copied().fold(f32::INFINITY, f32::min);
+            // the main solver comes first, the others are compared to it
+            let default_ms = *default_ms.get_or_insert(mean);
+            println!(
+                "  {name:<width$}  mean {mean:>10.4}ms  min {min:>10.4}ms  x{:.2}",
+                mean / default_ms
+            );
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Watch mode: run a day again whenever its input or its expected answers change
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use notify::{RecursiveMode, Watcher};
+
+use crate::answer::Answer;
+use crate::days::Day;
+use crate::inputs;
+use crate::parser::MyErr;
+
+/// Split answers written as text, the first line being part 1's answer and the rest being part 2's
+///
+/// Part 2 can span several lines, for answers which are drawn with ASCII art.
+pub fn split_answers(text: &str) -> [String; 2] {
+    let (part_1, part_2) = text.split_once('\n').unwrap_or((text, ""));
+    [part_1.trim().to_string(), part_2.trim().to_string()]
+}
+
+/// Read the expected answers, written like `split_answers` expects them
+pub fn read_answers(fp: &str) -> Option<[String; 2]> {
+    inputs::read(fp).ok().map(|text| split_answers(&text))
+}
+
+/// Parse the input and run both parts, printing how long each step took
+fn run_once<D: Day>(fp: &str) -> Result<[Answer; 2], MyErr> {
+    let start = Instant::now();
+    let input = D::parse_file(fp)?;
+    println!("Parsing took {}ms", start.elapsed().as_nanos() as f32 / 1e6);
+    let start = Instant::now();
+    let part_1 = D::part_1(&input).into();
+    println!("Part 1 took {}ms", start.elapsed().as_nanos() as f32 / 1e6);
+    let start = Instant::now();
+    let part_2 = D::part_2(&input).into();
+    println!("Part 2 took {}ms", start.elapsed().as_nanos() as f32 / 1e6);
+    Ok([part_1, part_2])
+}
+
+/// Print an answer, and how it compares with the previous run and the expected answer
+fn report(part: usize, answer: &Answer, previous: Option<&Answer>, expected: Option<&str>) {
+    println!("{}", answer.labelled(&format!("Part {part}")));
+    match previous {
+        Some(previous) if previous != answer => {
+            println!(
+                "  {}",
+                previous.labelled(&"changed, was".yellow().to_string())
+            );
+        }
+        Some(_) => println!("  {}", "unchanged".dimmed()),
+        None => {}
+    }
+    match expected {
+        // the answer of part 2 is not always known yet
+        Some("") | None => {}
+        Some(expected) if answer.matches(expected) => {
+            println!("  {}", "matches the expected answer".green());
+        }
+        Some(expected) => println!("  {} {expected}", "expected".red()),
+    }
+}
+
+/// Run the day, then run it again each time the input or answers file is written to, until the process is stopped
+pub fn watch<D: Day>(input_fp: &str, answers_fp: &str) {
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).expect("Could not watch the files");
+    // editors often replace files instead of writing to them, so we watch the whole directory
+    let dir = Path::new(input_fp).parent().unwrap_or(Path::new("."));
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .expect("Could not watch the inputs directory");
+    let encrypted = [input_fp, answers_fp].map(inputs::encrypted_fp);
+    let watched =
+        [input_fp, answers_fp, &encrypted[0], &encrypted[1]].map(|fp| Path::new(fp).file_name());
+
+    let mut previous: Option<[Answer; 2]> = None;
+    loop {
+        println!("======== {} ========", input_fp.bold());
+        match run_once::<D>(input_fp) {
+            Ok(answers) => {
+                let expected = read_answers(answers_fp);
+                for (i, answer) in answers.iter().enumerate() {
+                    report(
+                        i + 1,
+                        answer,
+                        previous.as_ref().map(|p| &p[i]),
+                        expected.as_ref().map(|e| e[i].as_str()),
+                    );
+                }
+                previous = Some(answers);
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+        println!("Watching for changes...");
+        // wait for a change to one of our files
+        loop {
+            let Ok(event) = events.recv() else {
+                return;
+            };
+            // reading the files ourselves must not trigger another run
+            if event.is_ok_and(|e| {
+                !e.kind.is_access() && e.paths.iter().any(|p| watched.contains(&p.file_name()))
+            }) {
+                break;
+            }
+        }
+        // a single save can trigger several events, we wait for them all before running again
+        while events.recv_timeout(Duration::from_millis(100)).is_ok() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn answers() {
+        let fp = std::env::temp_dir().join("aoc_2022_answers_test.txt");
+        fs::write(&fp, "42\n\n#..#\n#..#\n").unwrap();
+        let answers = read_answers(fp.to_str().unwrap()).unwrap();
+        assert_eq!(answers, ["42".to_string(), "#..#\n#..#".to_string()]);
+        fs::remove_file(&fp).unwrap();
+        assert_eq!(read_answers(fp.to_str().unwrap()), None);
+    }
+}